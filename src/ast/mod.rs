@@ -4,6 +4,10 @@ pub enum FunName {
     PenUp,
     // fn pen_down(&mut self);
     PenDown,
+    // fn pen_erase(&mut self);
+    PenErase,
+    // fn pen_reverse(&mut self);
+    PenReverse,
 
     // fn foreward(&mut self, pixel: Pixel);
     Foreward,
@@ -26,6 +30,52 @@ pub enum FunName {
     SetXCoordinate,
     // fn set_y_coordinate(&mut self, pixel: Pixel);
     SetYCoordinate,
+    // fn set_position(&mut self, x: Pixel, y: Pixel);
+    SetPosition,
+    // the same move written as SETPOS [ x y ]
+    SetPositionList,
+    // fn set_window(&mut self, min: Point, max: Point);
+    SetWindow,
+
+    // fn arc(&mut self, degree: Degree, radius: Pixel);
+    Arc,
+    // fn circle(&mut self, radius: Pixel);
+    Circle,
+    // fn bezier(&mut self, control1: Point, control2: Point, end: Point);
+    Bezier,
+
+    // fn label(&mut self, text: &str);
+    Label,
+    // fn set_label_font(&mut self, font: &str);
+    SetLabelFont,
+    // fn set_label_size(&mut self, size: Pixel);
+    SetLabelSize,
+
+    // fn tell(&mut self, id: TurtleId);
+    Tell,
+
+    // fn push_turtle(&mut self);
+    PushTurtle,
+    // fn pop_turtle(&mut self) -> Result<(), LogoError>;
+    PopTurtle,
+
+    // axiom, rules, then iterations step angle as expressions
+    LSystem,
+
+    // fn show_turtle(&mut self);
+    ShowTurtle,
+    // fn hide_turtle(&mut self);
+    HideTurtle,
+    // fn set_shape(&mut self, shape: TurtleShape);
+    SetShape,
+
+    // fn set_edge_mode(&mut self, mode: EdgeMode);
+    Wrap,
+    Fence,
+    Window,
+
+    // fn wait(&mut self, ticks: f32);
+    Wait,
 }
 
 // in expression
@@ -82,6 +132,8 @@ pub enum FunName {
 #[derive(Debug)]
 pub enum ASTNode {
     Sequence(Vec<ASTNode>),
+    // source line number, statement
+    Line(usize, Box<ASTNode>),
 
     // function name, argument
     FunctionCall(FunName, Option<Vec<String>>),
@@ -92,6 +144,8 @@ pub enum ASTNode {
     Expersion(String),
     If(Box<ASTNode>, Vec<ASTNode>),
     While(Box<ASTNode>, Vec<ASTNode>),
+    // turtle id, block run as that turtle
+    Ask(Box<ASTNode>, Vec<ASTNode>),
 
     // function name
     // expressions for arguments
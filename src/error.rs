@@ -1,6 +1,29 @@
+use std::cell::Cell;
+
 use miette::{miette, Severity};
 use thiserror::Error;
 
+thread_local! {
+    static KEEP_PARTIAL: Cell<bool> = const { Cell::new(false) };
+}
+
+// payload unwound by `fatal_error` inside `keep_partial`
+struct Stopped;
+
+// runs `f` with fatal errors reported but not exiting, so what `f` did until
+// then can still be saved; false when it stopped on an error
+pub fn keep_partial(f: impl FnOnce()) -> bool {
+    KEEP_PARTIAL.with(|keep| keep.set(true));
+    let ran = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    KEEP_PARTIAL.with(|keep| keep.set(false));
+    match ran {
+        Ok(()) => true,
+        Err(payload) if payload.is::<Stopped>() => false,
+        // a real panic carries on as usual
+        Err(payload) => std::panic::resume_unwind(payload),
+    }
+}
+
 // 定义一个错误类型
 #[derive(Error, Debug, Clone)]
 pub enum LogoError {
@@ -24,6 +47,39 @@ pub enum LogoError {
 
     #[error("Command: {0} need a expression, but current not a expression for string: {1}")]
     NotAexpression(String, String),
+
+    #[error("ADDASSIGN needs a number, but :{0} holds the list {1}")]
+    NotANumber(String, String),
+
+    #[error("Invalid turtle id: {0}")]
+    InvalidTurtle(String),
+
+    #[error("Invalid pen colour: {0}")]
+    InvalidColor(String),
+
+    #[error("POPTURTLE with no saved turtle state")]
+    EmptyTurtleStack,
+
+    #[error("invalid LSYSTEM: {0}")]
+    InvalidLSystem(String),
+
+    #[error("unknown turtle shape: {0}")]
+    InvalidShape(String),
+
+    #[error("turtle at ({0}, {1}) cannot move to ({2}, {3}), outside ({4}, {5}) to ({6}, {7})")]
+    OutOfBounds(f32, f32, f32, f32, f32, f32, f32, f32),
+
+    #[error("SETWINDOW {0} {1} {2} {3} is an empty window")]
+    InvalidWindow(f32, f32, f32, f32),
+
+    #[error("invalid trace, line {0}: {1}")]
+    InvalidTrace(usize, String),
+
+    #[error("could not save image {0}: {1}")]
+    SaveFailed(String, String),
+
+    #[error("PENERASE cannot erase on a transparent background")]
+    EraseOnTransparent,
 }
 
 pub fn fatal_error(error: LogoError) {
@@ -91,6 +147,109 @@ pub fn fatal_error(error: LogoError) {
             );
             eprintln!("failed to build since: {:?}", report);
         }
+        LogoError::NotANumber(..) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "add to each item of the list with its own variable",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::EmptyTurtleStack => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "every POPTURTLE needs a PUSHTURTLE before it",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidLSystem(_) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "rules look like F=F+F, iterations is a small whole number",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidShape(_) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "use \"triangle, \"arrow or a list of at least 3 x y corners",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::OutOfBounds(..) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "the canvas is fenced, use WINDOW or WRAP to leave it",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidTurtle(_) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "turtle id is a non-negative whole number",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidColor(_) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "pen colours are whole numbers from 0 to 15",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidWindow(..) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "SETWINDOW takes xmin ymin xmax ymax, each max above its min",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::InvalidTrace(..) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "replay reads traces written by rslogo run --emit-trace",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::SaveFailed(..) => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "check that the directory exists and can be written",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+        LogoError::EraseOnTransparent => {
+            let report = miette!(
+                severity = Severity::Error,
+                help = "leave out --transparent, or draw over with SETPENCOLOR \"0",
+                "{}",
+                error,
+            );
+            eprintln!("failed to build since: {:?}", report);
+        }
+    }
+    if KEEP_PARTIAL.with(|keep| keep.get()) {
+        // no panic hook runs, the report above is all the user sees
+        std::panic::resume_unwind(Box::new(Stopped));
     }
     std::process::exit(1);
 }
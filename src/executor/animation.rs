@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use super::{
+    canvas::{Canvas, Shape},
+    image::{suffixed, write_atomic, AnimationOptions, ImageFormat, ImageOptions},
+    raster, svg,
+};
+
+// where each frame ends in `shapes`: frame k shows everything before
+// `ends[k]`, a wait holds the picture for as many frames as it lasts
+fn frames(shapes: &[Shape], animation: &AnimationOptions) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut drawn = 0;
+    for (i, shape) in shapes.iter().enumerate() {
+        match shape {
+            Shape::Wait(seconds) => {
+                if drawn > 0 {
+                    ends.push(i);
+                    drawn = 0;
+                }
+                let frames = (seconds * animation.fps).round() as usize;
+                ends.extend(std::iter::repeat_n(i, frames));
+            }
+            Shape::Group(_) | Shape::EndGroup => {}
+            _ => {
+                drawn += 1;
+                if drawn == animation.segments_per_frame {
+                    ends.push(i + 1);
+                    drawn = 0;
+                }
+            }
+        }
+    }
+    // the last frame is the whole drawing
+    if ends.last() != Some(&shapes.len()) {
+        ends.push(shapes.len());
+    }
+    ends
+}
+
+// an animated svg, or a numbered png per frame next to `path`
+pub fn write(
+    canvas: &Canvas,
+    format: ImageFormat,
+    path: &Path,
+    options: &ImageOptions,
+) -> std::io::Result<()> {
+    let animation = options.animation.as_ref().expect("animation options");
+    let ends = frames(&canvas.shapes, animation);
+    match format {
+        ImageFormat::Svg => {
+            let svg = svg::animate(canvas, options, &ends, animation.fps);
+            write_atomic(path, svg.as_bytes())
+        }
+        ImageFormat::Png => {
+            let digits = ends.len().to_string().len().max(4);
+            let mut frame = Canvas::new(canvas.width, canvas.height);
+            let mut bytes = Vec::new();
+            for (i, end) in ends.iter().enumerate() {
+                // frames held by a wait are the same picture again
+                if bytes.is_empty() || frame.shapes.len() != *end {
+                    frame.shapes = canvas.shapes[..*end].to_vec();
+                    bytes = raster::write(&frame, options)?;
+                }
+                // `drawing-0001.png` for the first frame
+                let frame_path = suffixed(path, &format!("{:0digits$}", i + 1));
+                write_atomic(&frame_path, &bytes)?;
+            }
+            Ok(())
+        }
+        _ => Err(std::io::Error::other(
+            "only svg and png images can be animated",
+        )),
+    }
+}
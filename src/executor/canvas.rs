@@ -0,0 +1,481 @@
+use super::{image::Placement, Color, Degree, Direction, Pixel, Point};
+
+// what a pen stroke leaves on the canvas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ink {
+    Color(Color),
+    Erase,
+    Reverse,
+}
+
+// a primitive drawn on the canvas, in canvas coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        from: Point,
+        to: Point,
+        ink: Ink,
+    },
+    Arc {
+        center: Point,
+        radius: Pixel,
+        start: Direction,
+        sweep: Degree,
+        ink: Ink,
+    },
+    Bezier {
+        from: Point,
+        control1: Point,
+        control2: Point,
+        to: Point,
+        ink: Ink,
+    },
+    Polygon {
+        points: Vec<Point>,
+        color: Color,
+    },
+    Label {
+        at: Point,
+        direction: Direction,
+        text: String,
+        font: String,
+        size: Pixel,
+        color: Color,
+    },
+    // shapes up to the matching EndGroup belong to the named group
+    Group(String),
+    EndGroup,
+    // a pause in seconds when the drawing is animated
+    Wait(f32),
+}
+
+impl Shape {
+    // the pen a shape is drawn with, none for erasing, reversing and groups
+    fn color(&self) -> Option<Color> {
+        match self {
+            Shape::Line { ink, .. } | Shape::Arc { ink, .. } | Shape::Bezier { ink, .. } => {
+                match ink {
+                    Ink::Color(color) => Some(*color),
+                    Ink::Erase | Ink::Reverse => None,
+                }
+            }
+            Shape::Polygon { color, .. } | Shape::Label { color, .. } => Some(*color),
+            Shape::Group(_) | Shape::EndGroup | Shape::Wait(_) => None,
+        }
+    }
+}
+
+// a connected run of points drawn with one ink, curves already flattened
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub ink: Ink,
+    pub points: Vec<Point>,
+}
+
+#[derive(Clone)]
+pub struct Canvas {
+    pub(super) width: u32,
+    pub(super) height: u32,
+    pub(super) shapes: Vec<Shape>,
+    // source line each shape was drawn by, 0 when not known
+    pub(super) lines: Vec<usize>,
+    // text of the program, empty when not known
+    pub(super) source: String,
+    pub(super) line: usize,
+}
+
+// digits after the decimal point unless asked otherwise
+pub(super) const PRECISION: usize = 3;
+
+// shortest decimal form with at most `precision` decimals, keeps the svg small
+pub(super) fn number(v: f32, precision: usize) -> String {
+    let s = format!("{:.*}", precision, v);
+    let s = if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &s
+    };
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+// point at `direction` seen from `center`, 0 degrees being straight up
+pub(super) fn polar(center: Point, radius: Pixel, direction: Direction) -> Point {
+    let radian = (direction - 90.0).to_radians();
+    (
+        center.0 + radian.cos() * radius,
+        center.1 + radian.sin() * radius,
+    )
+}
+
+// segments to stay within a tenth of a pixel of an arc
+fn arc_steps(radius: Pixel, sweep: Degree) -> usize {
+    let step = 2.0 * (1.0 - 0.1 / radius.abs().max(0.1)).acos();
+    let steps = (sweep.to_radians().abs() / step.max(f32::EPSILON)).ceil();
+    (steps as usize).clamp(4, 720)
+}
+
+fn bezier_point(from: Point, control1: Point, control2: Point, to: Point, t: f32) -> Point {
+    let u = 1.0 - t;
+    let mix = |a: f32, b: f32, c: f32, d: f32| {
+        u * u * u * a + 3.0 * u * u * t * b + 3.0 * u * t * t * c + t * t * t * d
+    };
+    (
+        mix(from.0, control1.0, control2.0, to.0),
+        mix(from.1, control1.1, control2.1, to.1),
+    )
+}
+
+// `shapes` as polylines; labels are left out, turtle glyphs become closed outlines
+pub(super) fn strokes(shapes: &[Shape]) -> Vec<Stroke> {
+    let mut strokes = Vec::new();
+    for shape in shapes {
+        match shape {
+            Shape::Line { from, to, ink } => extend(&mut strokes, *ink, vec![*from, *to]),
+            Shape::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+                ink,
+            } => {
+                let sweep = sweep.clamp(-360.0, 360.0);
+                let steps = arc_steps(*radius, sweep);
+                let points = (0..=steps)
+                    .map(|i| polar(*center, *radius, start + sweep * i as f32 / steps as f32))
+                    .collect();
+                extend(&mut strokes, *ink, points);
+            }
+            Shape::Bezier {
+                from,
+                control1,
+                control2,
+                to,
+                ink,
+            } => {
+                let hull = [*from, *control1, *control2, *to]
+                    .windows(2)
+                    .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+                    .sum::<f32>();
+                let steps = ((hull / 2.0).ceil() as usize).clamp(4, 200);
+                let points = (0..=steps)
+                    .map(|i| {
+                        let t = i as f32 / steps as f32;
+                        bezier_point(*from, *control1, *control2, *to, t)
+                    })
+                    .collect();
+                extend(&mut strokes, *ink, points);
+            }
+            Shape::Polygon { points, color } => {
+                let mut outline = points.clone();
+                outline.extend(points.first());
+                strokes.push(Stroke {
+                    ink: Ink::Color(*color),
+                    points: outline,
+                });
+            }
+            Shape::Label { .. } | Shape::Group(_) | Shape::EndGroup | Shape::Wait(_) => {}
+        }
+    }
+    strokes
+}
+
+// adds `points` to the last stroke when it carries on from it
+fn extend(strokes: &mut Vec<Stroke>, ink: Ink, points: Vec<Point>) {
+    if let Some(last) = strokes.last_mut() {
+        if last.ink == ink && last.points.last() == points.first() {
+            last.points.extend_from_slice(&points[1..]);
+            return;
+        }
+    }
+    strokes.push(Stroke { ink, points });
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Canvas {
+            width,
+            height,
+            shapes: Vec::new(),
+            lines: Vec::new(),
+            source: String::new(),
+            line: 0,
+        }
+    }
+
+    // an empty canvas of the same size and program
+    pub(super) fn blank(&self) -> Canvas {
+        Canvas {
+            source: self.source.clone(),
+            ..Canvas::new(self.width, self.height)
+        }
+    }
+
+    // shapes drawn from now on come from `line` of the program
+    pub fn set_line(&mut self, line: usize) {
+        self.line = line;
+    }
+
+    pub(super) fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+        self.lines.push(self.line);
+    }
+
+    pub(super) fn line_of(&self, shape: usize) -> usize {
+        self.lines.get(shape).copied().unwrap_or(0)
+    }
+
+    pub fn line(&mut self, from: Point, to: Point, ink: Ink) {
+        self.push(Shape::Line { from, to, ink });
+    }
+
+    pub fn arc(&mut self, center: Point, radius: Pixel, start: Direction, sweep: Degree, ink: Ink) {
+        self.push(Shape::Arc {
+            center,
+            radius,
+            start,
+            sweep,
+            ink,
+        });
+    }
+
+    pub fn bezier(&mut self, from: Point, control1: Point, control2: Point, to: Point, ink: Ink) {
+        self.push(Shape::Bezier {
+            from,
+            control1,
+            control2,
+            to,
+            ink,
+        });
+    }
+
+    // a filled polygon, used for the turtle glyphs
+    pub fn polygon(&mut self, points: Vec<Point>, color: Color) {
+        self.push(Shape::Polygon { points, color });
+    }
+
+    pub fn label(
+        &mut self,
+        at: Point,
+        direction: Direction,
+        text: &str,
+        font: &str,
+        size: Pixel,
+        color: Color,
+    ) {
+        self.push(Shape::Label {
+            at,
+            direction,
+            text: text.to_string(),
+            font: font.to_string(),
+            size,
+            color,
+        });
+    }
+
+    pub fn group(&mut self, name: &str) {
+        self.push(Shape::Group(name.to_string()));
+    }
+
+    pub fn end_group(&mut self) {
+        self.push(Shape::EndGroup);
+    }
+
+    pub fn wait(&mut self, seconds: f32) {
+        self.push(Shape::Wait(seconds));
+    }
+
+    // smallest box around everything drawn, labels guessed from their size
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut points: Vec<Point> = strokes(&self.shapes)
+            .into_iter()
+            .flat_map(|stroke| stroke.points)
+            .collect();
+        for shape in &self.shapes {
+            if let Shape::Label {
+                at,
+                direction,
+                text,
+                size,
+                ..
+            } = shape
+            {
+                // glyphs about 0.6 of the size wide, reading along the heading
+                let length = text.chars().count() as f32 * size * 0.6;
+                let end = polar(*at, length, *direction);
+                let up = polar((0.0, 0.0), *size, direction - 90.0);
+                points.extend([
+                    *at,
+                    end,
+                    (at.0 + up.0, at.1 + up.1),
+                    (end.0 + up.0, end.1 + up.1),
+                ]);
+            }
+        }
+        let first = *points.first()?;
+        Some(points.iter().fold((first, first), |(min, max), p| {
+            (
+                (min.0.min(p.0), min.1.min(p.1)),
+                (max.0.max(p.0), max.1.max(p.1)),
+            )
+        }))
+    }
+
+    // the drawing scaled by `scale` and then moved by `offset`, on a canvas
+    // of `width` x `height`
+    fn transformed(&self, scale: f32, offset: Point, width: u32, height: u32) -> Canvas {
+        let map = |(x, y): Point| (x * scale + offset.0, y * scale + offset.1);
+        let shapes = self
+            .shapes
+            .iter()
+            .map(|shape| match shape.clone() {
+                Shape::Line { from, to, ink } => Shape::Line {
+                    from: map(from),
+                    to: map(to),
+                    ink,
+                },
+                Shape::Arc {
+                    center,
+                    radius,
+                    start,
+                    sweep,
+                    ink,
+                } => Shape::Arc {
+                    center: map(center),
+                    radius: radius * scale,
+                    start,
+                    sweep,
+                    ink,
+                },
+                Shape::Bezier {
+                    from,
+                    control1,
+                    control2,
+                    to,
+                    ink,
+                } => Shape::Bezier {
+                    from: map(from),
+                    control1: map(control1),
+                    control2: map(control2),
+                    to: map(to),
+                    ink,
+                },
+                Shape::Polygon { points, color } => Shape::Polygon {
+                    points: points.into_iter().map(map).collect(),
+                    color,
+                },
+                Shape::Label {
+                    at,
+                    direction,
+                    text,
+                    font,
+                    size,
+                    color,
+                } => Shape::Label {
+                    at: map(at),
+                    direction,
+                    text,
+                    font,
+                    size: size * scale,
+                    color,
+                },
+                shape => shape,
+            })
+            .collect();
+        Canvas {
+            width,
+            height,
+            shapes,
+            ..self.clone()
+        }
+    }
+
+    // the drawing moved onto the image as `placement` asks
+    pub fn placed(&self, placement: Placement, margin: f32) -> Canvas {
+        let Some((min, max)) = self.bounds().filter(|_| placement != Placement::AsDrawn) else {
+            return self.clone();
+        };
+        let size = (max.0 - min.0, max.1 - min.1);
+        match placement {
+            Placement::AsDrawn => unreachable!("left as drawn above"),
+            Placement::Fit => {
+                let room = (
+                    (self.width as f32 - 2.0 * margin).max(1.0),
+                    (self.height as f32 - 2.0 * margin).max(1.0),
+                );
+                // a line or a dot has no size to scale along
+                let scale = [(room.0, size.0), (room.1, size.1)]
+                    .into_iter()
+                    .filter(|(_, size)| *size > f32::EPSILON)
+                    .map(|(room, size)| room / size)
+                    .reduce(f32::min)
+                    .unwrap_or(1.0);
+                let offset = (
+                    (self.width as f32 - size.0 * scale) / 2.0 - min.0 * scale,
+                    (self.height as f32 - size.1 * scale) / 2.0 - min.1 * scale,
+                );
+                self.transformed(scale, offset, self.width, self.height)
+            }
+            Placement::AutoSize => {
+                let width = (size.0 + 2.0 * margin).ceil().max(1.0) as u32;
+                let height = (size.1 + 2.0 * margin).ceil().max(1.0) as u32;
+                self.transformed(1.0, (margin - min.0, margin - min.1), width, height)
+            }
+        }
+    }
+
+    // whether a pen erased anything, which only shows on the background
+    pub fn erases(&self) -> bool {
+        self.shapes.iter().any(|shape| {
+            matches!(
+                shape,
+                Shape::Line {
+                    ink: Ink::Erase,
+                    ..
+                } | Shape::Arc {
+                    ink: Ink::Erase,
+                    ..
+                } | Shape::Bezier {
+                    ink: Ink::Erase,
+                    ..
+                }
+            )
+        })
+    }
+
+    // everything drawn as polylines, for outputs without curves or text
+    pub fn strokes(&self) -> Vec<Stroke> {
+        strokes(&self.shapes)
+    }
+
+    // one canvas per pen colour, in the order the pens were first used;
+    // erasing, reversing and groups go to every layer as they apply to each
+    pub fn layers(&self) -> Vec<(Color, Canvas)> {
+        let mut layers: Vec<(Color, Canvas)> = Vec::new();
+        for color in self.shapes.iter().filter_map(Shape::color) {
+            if !layers.iter().any(|(c, _)| *c == color) {
+                layers.push((color, self.blank()));
+            }
+        }
+        for (i, shape) in self.shapes.iter().enumerate() {
+            for (color, layer) in &mut layers {
+                match shape {
+                    // a group with nothing of this colour in it is left out
+                    Shape::EndGroup if matches!(layer.shapes.last(), Some(Shape::Group(_))) => {
+                        layer.shapes.pop();
+                        layer.lines.pop();
+                    }
+                    _ if shape.color().is_none_or(|c| c == *color) => {
+                        layer.set_line(self.line_of(i));
+                        layer.push(shape.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        layers
+    }
+}
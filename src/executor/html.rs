@@ -0,0 +1,159 @@
+use super::{
+    canvas::Canvas,
+    image::ImageOptions,
+    svg::{self, escape, BACKGROUND},
+    trace::json_string,
+};
+
+// steps as a javascript array of [line, markup]; `<` escaped so nothing in
+// them can close the script element
+fn steps(canvas: &Canvas, options: &ImageOptions) -> String {
+    let elements = svg::elements(canvas, options);
+    let steps: Vec<String> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| !element.is_empty())
+        .map(|(i, element)| format!("[{},{}]", canvas.line_of(i), json_string(element)))
+        .collect();
+    format!("[\n{}\n]", steps.join(",\n")).replace('<', "\\u003c")
+}
+
+// the program, one element per line so the player can point at it
+fn source(canvas: &Canvas) -> String {
+    canvas
+        .source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("<span id=\"line-{}\">{}</span>", i + 1, escape(line)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 1em; display: flex; gap: 1em; flex-wrap: wrap; }
+#controls { display: flex; gap: 0.5em; align-items: center; margin-top: 0.5em; }
+#step { flex: 1; }
+#source { margin: 0; padding: 0.5em; background: #f4f4f4; max-height: 90vh; overflow: auto; }
+#source span { display: block; cursor: pointer; }
+#source span:hover { background: #e4e4e4; }
+#source .current { background: #ffe08a; }
+";
+
+// step n shows the first n steps and marks the line that drew the last one
+const PLAYER: &str = "\
+const drawing = document.getElementById('drawing');
+const slider = document.getElementById('step');
+const count = document.getElementById('count');
+const play = document.getElementById('play');
+let timer = null;
+slider.max = steps.length;
+
+function show(n) {
+  n = Math.max(0, Math.min(steps.length, n));
+  slider.value = n;
+  drawing.innerHTML = steps.slice(0, n).map(step => step[1]).join('');
+  count.textContent = n + ' / ' + steps.length;
+  for (const line of document.querySelectorAll('#source .current')) {
+    line.classList.remove('current');
+  }
+  const line = n > 0 && document.getElementById('line-' + steps[n - 1][0]);
+  if (line) {
+    line.classList.add('current');
+    line.scrollIntoView({ block: 'nearest' });
+  }
+}
+
+function stop() {
+  clearInterval(timer);
+  timer = null;
+  play.textContent = 'Play';
+}
+
+slider.addEventListener('input', () => { stop(); show(Number(slider.value)); });
+document.getElementById('back').addEventListener('click', () => { stop(); show(Number(slider.value) - 1); });
+document.getElementById('next').addEventListener('click', () => { stop(); show(Number(slider.value) + 1); });
+play.addEventListener('click', () => {
+  if (timer) {
+    stop();
+    return;
+  }
+  if (Number(slider.value) >= steps.length) {
+    show(0);
+  }
+  play.textContent = 'Pause';
+  timer = setInterval(() => {
+    show(Number(slider.value) + 1);
+    if (Number(slider.value) >= steps.length) {
+      stop();
+    }
+  }, 100);
+});
+document.addEventListener('keydown', event => {
+  if (event.key === 'ArrowLeft') { stop(); show(Number(slider.value) - 1); }
+  if (event.key === 'ArrowRight') { stop(); show(Number(slider.value) + 1); }
+});
+// clicking a line jumps to the last step it drew
+for (const line of document.querySelectorAll('#source span')) {
+  line.addEventListener('click', () => {
+    const number = Number(line.id.slice('line-'.length));
+    const last = steps.map(step => step[0]).lastIndexOf(number);
+    if (last >= 0) {
+      stop();
+      show(last + 1);
+    }
+  });
+}
+show(steps.length);
+";
+
+// a single page with the drawing, the program and a player stepping
+// through what each line drew; nothing outside the file is needed
+pub fn write(canvas: &Canvas, options: &ImageOptions) -> String {
+    let (width, height) = options.scaled(canvas.width, canvas.height);
+    let background = if options.transparent {
+        String::new()
+    } else {
+        format!(
+            "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{BACKGROUND}\"/>",
+            canvas.width, canvas.height
+        )
+    };
+    let source = if canvas.source.is_empty() {
+        String::new()
+    } else {
+        format!("<pre id=\"source\">{}</pre>\n", source(canvas))
+    };
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>rslogo</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+<div>
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {} {}\">
+{background}<g id=\"drawing\"></g>
+</svg>
+<div id=\"controls\">
+<button id=\"back\">&#9664;</button>
+<button id=\"play\">Play</button>
+<button id=\"next\">&#9654;</button>
+<input id=\"step\" type=\"range\" min=\"0\">
+<span id=\"count\"></span>
+</div>
+</div>
+{source}<script>
+const steps = {};
+{PLAYER}</script>
+</body>
+</html>
+",
+        canvas.width,
+        canvas.height,
+        steps(canvas, options),
+    )
+}
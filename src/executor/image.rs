@@ -0,0 +1,189 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::{
+    canvas::{Canvas, PRECISION},
+    html, plotter, print, raster, svg,
+};
+
+// how the image is written, whatever its format
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageOptions {
+    // decimals kept for svg coordinates
+    pub precision: usize,
+    // wrap each procedure called by the main program in an svg group
+    pub groups: bool,
+    // one inkscape layer per pen colour in svg files
+    pub layers: bool,
+    // one file per pen colour, its number added to the file name
+    pub split_colors: bool,
+    // output size relative to the canvas
+    pub scale: f32,
+    // png samples taken across each pixel, in both directions
+    pub supersample: u32,
+    pub antialias: bool,
+    // leave out the black background
+    pub transparent: bool,
+    // resolution stored in png files
+    pub dpi: f32,
+    // where the drawing goes on the image, `margin` pixels from its edges
+    pub placement: Placement,
+    pub margin: f32,
+    // tidy and reorder paths before writing, simplifying within this many pixels
+    pub optimize: Option<f32>,
+    // play the drawing back in execution order instead of the final picture
+    pub animation: Option<AnimationOptions>,
+    // draw the image in the terminal as well
+    pub preview: bool,
+    // write the image file, only left out with a preview
+    pub save: bool,
+    pub plotter: PlotterOptions,
+}
+
+// where the drawing ends up on the image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    // where the turtles drew it, on the canvas asked for
+    AsDrawn,
+    // scaled and centred to fill the canvas
+    Fit,
+    // on an image the size of the drawing
+    AutoSize,
+}
+
+// how fast an animation adds to the drawing
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationOptions {
+    pub fps: f32,
+    // lines, curves and labels added by each frame
+    pub segments_per_frame: usize,
+}
+
+// pen plotter settings, lengths in millimetres
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotterOptions {
+    pub paper_width: f32,
+    pub paper_height: f32,
+    // millimetres per minute while drawing
+    pub feed_rate: f32,
+    // g-code lines lifting and lowering the pen, and pausing for a new one
+    pub pen_up: String,
+    pub pen_down: String,
+    pub pen_change: String,
+}
+
+impl Default for PlotterOptions {
+    fn default() -> Self {
+        // a4 portrait, pen held on the z axis
+        PlotterOptions {
+            paper_width: 210.0,
+            paper_height: 297.0,
+            feed_rate: 3000.0,
+            pen_up: "G0 Z5".to_string(),
+            pen_down: "G1 Z0".to_string(),
+            pen_change: "M0".to_string(),
+        }
+    }
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            precision: PRECISION,
+            groups: false,
+            layers: false,
+            split_colors: false,
+            scale: 1.0,
+            supersample: 1,
+            antialias: true,
+            transparent: false,
+            dpi: 96.0,
+            placement: Placement::AsDrawn,
+            margin: 10.0,
+            optimize: None,
+            animation: None,
+            preview: false,
+            save: true,
+            plotter: PlotterOptions::default(),
+        }
+    }
+}
+
+impl ImageOptions {
+    // image size in pixels for a canvas of `width` x `height`
+    pub fn scaled(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |size: u32| ((size as f32 * self.scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+}
+
+// what the image file holds, picked from its extension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+    GCode,
+    Hpgl,
+    Pdf,
+    Eps,
+    Html,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("svg") => Some(ImageFormat::Svg),
+            Some("png") => Some(ImageFormat::Png),
+            Some("gcode" | "nc") => Some(ImageFormat::GCode),
+            Some("hpgl" | "plt") => Some(ImageFormat::Hpgl),
+            Some("pdf") => Some(ImageFormat::Pdf),
+            Some("eps") => Some(ImageFormat::Eps),
+            Some("html" | "htm") => Some(ImageFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, canvas: &Canvas, options: &ImageOptions) -> std::io::Result<Vec<u8>> {
+        match self {
+            ImageFormat::Svg => Ok(svg::write(canvas, options).into_bytes()),
+            ImageFormat::Png => raster::write(canvas, options),
+            ImageFormat::GCode => Ok(plotter::gcode(canvas, options).into_bytes()),
+            ImageFormat::Hpgl => Ok(plotter::hpgl(canvas, options).into_bytes()),
+            ImageFormat::Pdf => Ok(print::pdf(canvas, options)),
+            ImageFormat::Eps => Ok(print::eps(canvas, options)),
+            ImageFormat::Html => Ok(html::write(canvas, options).into_bytes()),
+        }
+    }
+}
+
+// `drawing.png` becomes `drawing-{suffix}.png`, for images written as a set
+pub(super) fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!("-{suffix}"));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+// writes next to `path` first and renames, so a failed save never leaves half an image
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::other("not a file path"))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let written = std::fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    let renamed = written.and_then(|_| std::fs::rename(&temp, path));
+    if renamed.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    renamed
+}
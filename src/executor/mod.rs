@@ -1,44 +1,199 @@
+mod animation;
+mod canvas;
+mod html;
+mod image;
+mod optimize;
+mod plotter;
+mod preview;
+mod print;
+mod raster;
+mod recording;
+mod screen;
+mod svg;
+mod trace;
 mod turtle;
+mod world;
 
 // 0 <= Color <= 15
 pub type Color = u32;
 
 pub type Pixel = f32;
 
-pub type Degree = i32;
+pub type Degree = f32;
 
-pub type Direction = i32;
+pub type Direction = f32;
 
-use crate::executor::turtle::Turtle;
+pub type Point = (Pixel, Pixel);
+
+pub type TurtleId = u32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PenMode {
+    Up,
+    Paint,
+    // draws with the background colour, hiding what is underneath
+    Erase,
+    // inverts what is underneath, drawing twice restores it
+    Reverse,
+}
+
+// what happens when a turtle reaches the canvas border
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeMode {
+    // reappear at the opposite edge
+    Wrap,
+    // refuse to move off the canvas
+    Fence,
+    // move freely, whatever is off the canvas is clipped
+    Window,
+}
+
+// how the coordinates of SETX, SETY, SETXY, XCOR and YCOR are read
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Coordinates {
+    // canvas pixels, origin at the top left and y pointing down
+    Canvas,
+    // origin at the centre of the canvas and y pointing up, as in most logos
+    #[value(alias = "center")]
+    Centre,
+}
+
+impl Coordinates {
+    pub fn name(self) -> &'static str {
+        match self {
+            Coordinates::Canvas => "canvas",
+            Coordinates::Centre => "centre",
+        }
+    }
+}
+
+use crate::error::LogoError;
+use crate::executor::canvas::Shape;
+pub use crate::executor::image::{
+    AnimationOptions, ImageFormat, ImageOptions, Placement, PlotterOptions,
+};
+use crate::executor::recording::RecordingExecutor;
+use crate::executor::screen::Screen;
+use crate::executor::trace::TraceExecutor;
+pub use crate::executor::trace::TraceFormat;
+pub use crate::executor::turtle::TurtleShape;
 
 pub trait Executor {
+    // line of the statement about to run, only used for diagnostics
+    fn set_source_line(&mut self, _line: usize) {}
+    // text of the program being run, for outputs that show it
+    fn set_source(&mut self, _source: &str) {}
+
     fn pen_up(&mut self);
     fn pen_down(&mut self);
+    fn pen_erase(&mut self);
+    fn pen_reverse(&mut self);
 
-    fn foreward(&mut self, pixel: Pixel);
-    fn back(&mut self, pixel: Pixel);
-    fn left(&mut self, pixel: Pixel);
-    fn right(&mut self, pixel: Pixel);
+    fn foreward(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    fn back(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    fn left(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    fn right(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    // moves forward without drawing, whatever the pen is
+    fn jump(&mut self, pixel: Pixel) -> Result<(), LogoError>;
 
     fn set_color(&mut self, color: Color);
 
     fn turn(&mut self, degree: Degree);
     fn set_heading(&mut self, degree: Degree);
 
-    fn set_x_coordinate(&mut self, pixel: Pixel);
-    fn set_y_coordinate(&mut self, pixel: Pixel);
+    fn set_x_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    fn set_y_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError>;
+    // moves to an absolute position, drawing when the pen is down
+    fn set_position(&mut self, x: Pixel, y: Pixel) -> Result<(), LogoError>;
+
+    fn set_edge_mode(&mut self, mode: EdgeMode);
+
+    // picks the coordinate convention, before the program runs
+    fn set_coordinates(&mut self, coordinates: Coordinates);
+    // maps the world rectangle from `min` to `max` onto the canvas, distances
+    // and positions are in its units from then on
+    fn set_window(&mut self, min: Point, max: Point) -> Result<(), LogoError>;
+
+    // arc of a circle centred on the turtle, clockwise from its heading;
+    // the turtle itself does not move
+    fn arc(&mut self, degree: Degree, radius: Pixel);
+    fn circle(&mut self, radius: Pixel);
+    // cubic bezier curve from the turtle position, the turtle ends at `end`
+    fn bezier(&mut self, control1: Point, control2: Point, end: Point) -> Result<(), LogoError>;
+
+    // text at the turtle position, rotated to its heading
+    fn label(&mut self, text: &str);
+    fn set_label_font(&mut self, font: &str);
+    fn set_label_size(&mut self, size: Pixel);
+
+    // makes `id` the active turtle, creating it on first use
+    fn tell(&mut self, id: TurtleId);
+
+    // saves position, heading, pen and colour of the active turtle
+    fn push_turtle(&mut self);
+    fn pop_turtle(&mut self) -> Result<(), LogoError>;
+
+    // shown turtles are drawn on top of the final image
+    fn show_turtle(&mut self);
+    fn hide_turtle(&mut self);
+    fn set_shape(&mut self, shape: TurtleShape);
 
     fn get_x_coordinate(&self) -> Pixel;
     fn get_y_coordinate(&self) -> Pixel;
 
     fn get_heading(&self) -> Direction;
     fn get_color(&self) -> Color;
+    fn get_pen_mode(&self) -> PenMode;
+    fn who(&self) -> TurtleId;
+    fn is_shown(&self) -> bool;
+    // everything drawn so far, in canvas pixels and drawing order
+    fn shapes(&self) -> &[Shape];
+
+    // heading the turtle has to face to reach (x, y)
+    fn towards(&self, x: Pixel, y: Pixel) -> Direction;
+    fn distance(&self, x: Pixel, y: Pixel) -> Pixel;
+
+    // pauses an animation of the drawing for `ticks` sixtieths of a second
+    fn wait(&mut self, ticks: f32);
+
+    // what is drawn until end_group belongs to the named procedure call
+    fn begin_group(&mut self, name: &str);
+    fn end_group(&mut self);
+
+    // flags the active turtle position when the program stopped on an error
+    fn mark_stopped(&mut self);
+
+    // writes the image, called once after the program ran
+    fn finish(&mut self) -> Result<(), LogoError>;
 }
 
 pub struct ExecutorFactory;
 
 impl ExecutorFactory {
-    pub fn create_turtle(width: u32, height: u32, path: std::path::PathBuf) -> Box<dyn Executor> {
-        Box::new(Turtle::new(width, height, path))
+    pub fn create_screen(
+        width: u32,
+        height: u32,
+        path: std::path::PathBuf,
+        format: ImageFormat,
+        options: ImageOptions,
+    ) -> Box<dyn Executor> {
+        Box::new(Screen::new(width, height, path, format, options))
+    }
+
+    // reports every command run on `inner` to `out`
+    pub fn create_tracer(
+        inner: Box<dyn Executor>,
+        format: TraceFormat,
+        out: Box<dyn std::io::Write>,
+    ) -> Box<dyn Executor> {
+        Box::new(TraceExecutor::new(inner, format, out))
+    }
+
+    // writes the drawing operations run on `inner` to `out`, one per line
+    pub fn create_recorder(
+        inner: Box<dyn Executor>,
+        out: Box<dyn std::io::Write>,
+    ) -> Box<dyn Executor> {
+        Box::new(RecordingExecutor::new(inner, out))
     }
 }
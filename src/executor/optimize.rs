@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+
+use super::{
+    canvas::{strokes, Canvas, Ink, Shape, Stroke},
+    Point,
+};
+
+// points closer than this, in pixels, are the same point
+const EPSILON: f32 = 1e-3;
+
+fn distance(a: Point, b: Point) -> f32 {
+    (b.0 - a.0).hypot(b.1 - a.1)
+}
+
+// distance from `p` to the segment between `a` and `b`
+fn segment_distance(p: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return distance(p, a);
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0);
+    distance(p, (a.0 + dx * t, a.1 + dy * t))
+}
+
+// the same for a segment whichever way round it was drawn
+fn segment_key(from: Point, to: Point) -> [(i64, i64); 2] {
+    let grid = |(x, y): Point| ((x / EPSILON).round() as i64, (y / EPSILON).round() as i64);
+    let mut key = [grid(from), grid(to)];
+    key.sort();
+    key
+}
+
+// zero-length and repeated segments dropped, paths split where a repeat was
+fn dedupe(strokes: &[Stroke]) -> Vec<Vec<Point>> {
+    let mut seen = HashSet::new();
+    let mut paths = Vec::new();
+    for stroke in strokes {
+        let mut path: Vec<Point> = Vec::new();
+        for segment in stroke.points.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            if distance(from, to) < EPSILON {
+                continue;
+            }
+            if !seen.insert(segment_key(from, to)) {
+                if path.len() > 1 {
+                    paths.push(std::mem::take(&mut path));
+                }
+                path.clear();
+                continue;
+            }
+            if path.is_empty() {
+                path.push(from);
+            }
+            path.push(to);
+        }
+        if path.len() > 1 {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+// points lying on the line between their neighbours carry nothing,
+// `points` being at least one segment
+fn merge_collinear(points: &[Point]) -> Vec<Point> {
+    let mut merged = vec![points[0]];
+    for window in points.windows(3) {
+        let previous = *merged.last().expect("first point");
+        if segment_distance(window[1], previous, window[2]) >= EPSILON {
+            merged.push(window[1]);
+        }
+    }
+    merged.push(points[points.len() - 1]);
+    merged
+}
+
+// douglas-peucker: keeps the farthest point while it strays more than
+// `tolerance` from the chord, `out` already holding the first point
+fn simplify(points: &[Point], tolerance: f32, out: &mut Vec<Point>) {
+    let (first, last) = (points[0], points[points.len() - 1]);
+    let farthest = (1..points.len() - 1)
+        .map(|i| (i, segment_distance(points[i], first, last)))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    match farthest {
+        Some((i, d)) if d > tolerance => {
+            simplify(&points[..=i], tolerance, out);
+            simplify(&points[i..], tolerance, out);
+        }
+        _ => out.push(last),
+    }
+}
+
+// nearest path next, drawn backwards when its end is closer
+fn reorder(mut paths: Vec<Vec<Point>>, mut pen: Point) -> Vec<Vec<Point>> {
+    let mut ordered = Vec::with_capacity(paths.len());
+    while !paths.is_empty() {
+        let (i, reverse, _) = paths
+            .iter()
+            .enumerate()
+            .flat_map(|(i, path)| {
+                let end = *path.last().expect("path end");
+                [
+                    (i, false, distance(pen, path[0])),
+                    (i, true, distance(pen, end)),
+                ]
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .expect("a path left");
+        let mut path = paths.swap_remove(i);
+        if reverse {
+            path.reverse();
+        }
+        pen = *path.last().expect("path end");
+        ordered.push(path);
+    }
+    ordered
+}
+
+// lines, arcs and curves between the shapes that keep their place
+fn drawable(shape: &Shape) -> bool {
+    matches!(
+        shape,
+        Shape::Line { .. } | Shape::Arc { .. } | Shape::Bezier { .. }
+    )
+}
+
+struct Optimizer {
+    canvas: Canvas,
+    tolerance: f32,
+    // where the last path ended
+    pen: Option<Point>,
+}
+
+impl Optimizer {
+    fn push(&mut self, ink: Ink, points: &[Point]) {
+        for segment in points.windows(2) {
+            self.canvas.line(segment[0], segment[1], ink);
+        }
+        self.pen = points.last().copied().or(self.pen);
+    }
+
+    fn section(&mut self, shapes: &[Shape]) {
+        // rebuilt paths no longer come from one line of the program
+        self.canvas.set_line(0);
+        let strokes = strokes(shapes);
+        for run in strokes.chunk_by(|a, b| a.ink == b.ink) {
+            let ink = run[0].ink;
+            // erasing and reversing depend on exactly what is under them
+            if !matches!(ink, Ink::Color(_)) {
+                for stroke in run {
+                    self.push(ink, &stroke.points);
+                }
+                continue;
+            }
+
+            // merged first so a side drawn in pieces matches the same side drawn whole
+            let merged: Vec<Stroke> = run
+                .iter()
+                .map(|stroke| Stroke {
+                    ink,
+                    points: merge_collinear(&stroke.points),
+                })
+                .collect();
+            let paths: Vec<Vec<Point>> = dedupe(&merged)
+                .iter()
+                .map(|path| {
+                    let mut simplified = vec![path[0]];
+                    simplify(path, self.tolerance, &mut simplified);
+                    simplified
+                })
+                .collect();
+            let Some(start) = self.pen.or(paths.first().map(|path| path[0])) else {
+                continue;
+            };
+            for path in reorder(paths, start) {
+                self.push(ink, &path);
+            }
+        }
+    }
+}
+
+// the canvas tidied for plotting: each run of one colour loses repeated and
+// needless points, is simplified within `tolerance` pixels and reordered to
+// cut pen-up travel; curves come out as lines, everything else keeps its place
+pub fn optimize(canvas: &Canvas, tolerance: f32) -> Canvas {
+    let mut optimizer = Optimizer {
+        canvas: canvas.blank(),
+        tolerance,
+        pen: None,
+    };
+    let mut start = 0;
+    for (i, shape) in canvas.shapes.iter().enumerate() {
+        if !drawable(shape) {
+            optimizer.section(&canvas.shapes[start..i]);
+            optimizer.canvas.set_line(canvas.line_of(i));
+            optimizer.canvas.push(shape.clone());
+            start = i + 1;
+        }
+    }
+    optimizer.section(&canvas.shapes[start..]);
+    optimizer.canvas
+}
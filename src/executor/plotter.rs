@@ -0,0 +1,191 @@
+use super::{
+    canvas::{number, Canvas, Ink},
+    image::ImageOptions,
+    svg::hex,
+    Color, Point,
+};
+
+// hpgl plotter units per millimetre
+const HPGL_UNITS: f32 = 40.0;
+
+// canvas pixels to paper millimetres: the drawing is scaled to fit the paper
+// and centred on it, y pointing up as plotters expect
+struct Paper {
+    scale: f32,
+    offset: Point,
+    height: f32,
+}
+
+impl Paper {
+    fn new(canvas: &Canvas, options: &ImageOptions) -> Self {
+        let (width, height) = (canvas.width as f32, canvas.height as f32);
+        let plotter = &options.plotter;
+        let scale = (plotter.paper_width / width).min(plotter.paper_height / height);
+        Paper {
+            scale,
+            offset: (
+                (plotter.paper_width - width * scale) / 2.0,
+                (plotter.paper_height - height * scale) / 2.0,
+            ),
+            height,
+        }
+    }
+
+    fn map(&self, (x, y): Point) -> Point {
+        (
+            self.offset.0 + x * self.scale,
+            self.offset.1 + (self.height - y) * self.scale,
+        )
+    }
+}
+
+// the part of a segment inside the canvas, Liang-Barsky style
+fn clip_segment(from: Point, to: Point, width: f32, height: f32) -> Option<(Point, Point)> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let (mut enter, mut leave) = (0.0f32, 1.0f32);
+    for (p, q) in [
+        (-dx, from.0),
+        (dx, width - from.0),
+        (-dy, from.1),
+        (dy, height - from.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                enter = enter.max(t);
+            } else {
+                leave = leave.min(t);
+            }
+        }
+    }
+    if enter > leave {
+        return None;
+    }
+    let at = |t: f32| (from.0 + dx * t, from.1 + dy * t);
+    Some((at(enter), at(leave)))
+}
+
+// pen strokes cut to the canvas, a plotter must never leave the paper;
+// plotters only have their pens, erasing and reversing are left out
+fn pen_strokes(canvas: &Canvas) -> Vec<(Color, Vec<Point>)> {
+    let (width, height) = (canvas.width as f32, canvas.height as f32);
+    let mut strokes: Vec<(Color, Vec<Point>)> = Vec::new();
+    for stroke in canvas.strokes() {
+        let Ink::Color(color) = stroke.ink else {
+            continue;
+        };
+        let mut joined = false;
+        for segment in stroke.points.windows(2) {
+            let Some((from, to)) = clip_segment(segment[0], segment[1], width, height) else {
+                joined = false;
+                continue;
+            };
+            match strokes.last_mut() {
+                Some((_, points)) if joined && points.last() == Some(&from) => points.push(to),
+                _ => strokes.push((color, vec![from, to])),
+            }
+            // the next segment carries on unless this one was cut short
+            joined = to == segment[1];
+        }
+    }
+    strokes
+}
+
+fn mm(v: f32) -> String {
+    number(v, 3)
+}
+
+// g-code in millimetres, pen lifted and lowered by the configured commands
+pub fn gcode(canvas: &Canvas, options: &ImageOptions) -> String {
+    let plotter = &options.plotter;
+    let paper = Paper::new(canvas, options);
+    let strokes = pen_strokes(canvas);
+
+    let mut out = format!(
+        "; rslogo {}x{} canvas on {}x{} mm paper\n",
+        canvas.width,
+        canvas.height,
+        mm(plotter.paper_width),
+        mm(plotter.paper_height)
+    );
+    if let Some((color, _)) = strokes.first() {
+        out.push_str(&format!(
+            "; start with the pen for colour {color} ({})\n",
+            hex(*color)
+        ));
+    }
+    out.push_str("G21\nG90\n");
+    out.push_str(&format!("{}\n", plotter.pen_up));
+
+    let mut pen = strokes.first().map(|(color, _)| *color);
+    for (color, points) in &strokes {
+        if pen != Some(*color) {
+            out.push_str(&format!(
+                "{} ; change to the pen for colour {color} ({})\n",
+                plotter.pen_change,
+                hex(*color)
+            ));
+            pen = Some(*color);
+        }
+        let (x, y) = paper.map(points[0]);
+        out.push_str(&format!("G0 X{} Y{}\n", mm(x), mm(y)));
+        out.push_str(&format!("{}\n", plotter.pen_down));
+        for (i, point) in points[1..].iter().enumerate() {
+            let (x, y) = paper.map(*point);
+            // feed rate is modal, once per stroke is enough
+            if i == 0 {
+                out.push_str(&format!(
+                    "G1 X{} Y{} F{}\n",
+                    mm(x),
+                    mm(y),
+                    mm(plotter.feed_rate)
+                ));
+            } else {
+                out.push_str(&format!("G1 X{} Y{}\n", mm(x), mm(y)));
+            }
+        }
+        out.push_str(&format!("{}\n", plotter.pen_up));
+    }
+
+    out.push_str("G0 X0 Y0\nM2\n");
+    out
+}
+
+// hpgl, each colour gets the next pen in the carousel in order of first use
+pub fn hpgl(canvas: &Canvas, options: &ImageOptions) -> String {
+    let paper = Paper::new(canvas, options);
+    let units = |point: Point| {
+        let (x, y) = paper.map(point);
+        format!(
+            "{},{}",
+            (x * HPGL_UNITS).round() as i32,
+            (y * HPGL_UNITS).round() as i32
+        )
+    };
+
+    // feed rate is in mm per minute, velocity in cm per second
+    let mut out = format!("IN;VS{};\n", mm(options.plotter.feed_rate / 600.0));
+    let mut pens: Vec<Color> = Vec::new();
+    let mut pen = None;
+    for (color, points) in pen_strokes(canvas) {
+        if pen != Some(color) {
+            let number = match pens.iter().position(|c| *c == color) {
+                Some(i) => i + 1,
+                None => {
+                    pens.push(color);
+                    pens.len()
+                }
+            };
+            out.push_str(&format!("SP{number};\n"));
+            pen = Some(color);
+        }
+        let rest: Vec<String> = points[1..].iter().map(|p| units(*p)).collect();
+        out.push_str(&format!("PU{};PD{};\n", units(points[0]), rest.join(",")));
+    }
+    out.push_str("PU;SP0;\n");
+    out
+}
@@ -0,0 +1,203 @@
+use std::io::IsTerminal;
+
+use super::{
+    canvas::{Canvas, Ink, Shape},
+    Color, Point,
+};
+
+// a braille character is two dots wide and four high
+const DOTS_ACROSS: usize = 2;
+const DOTS_DOWN: usize = 4;
+
+// bit of each dot in a braille character, by column then row
+const BRAILLE: [[u8; DOTS_DOWN]; DOTS_ACROSS] =
+    [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+const WHITE: Color = 7;
+
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    dots: u8,
+    // colour of the last dot drawn in the cell, a cell shows one colour
+    color: Option<Color>,
+    // a label character replaces the dots
+    text: Option<char>,
+}
+
+struct Grid {
+    columns: usize,
+    rows: usize,
+    cells: Vec<Cell>,
+    // canvas pixels to dots
+    scale: f32,
+}
+
+impl Grid {
+    fn dot(&mut self, (x, y): Point, ink: Ink) {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let (column, row) = (x / DOTS_ACROSS, y / DOTS_DOWN);
+        if column >= self.columns || row >= self.rows {
+            return;
+        }
+        let bit = BRAILLE[x % DOTS_ACROSS][y % DOTS_DOWN];
+        let cell = &mut self.cells[row * self.columns + column];
+        match ink {
+            Ink::Color(color) => {
+                cell.dots |= bit;
+                cell.color = Some(color);
+            }
+            Ink::Erase => cell.dots &= !bit,
+            // reversing the black background shows white
+            Ink::Reverse => {
+                cell.dots ^= bit;
+                cell.color.get_or_insert(WHITE);
+            }
+        }
+    }
+
+    fn line(&mut self, from: Point, to: Point, ink: Ink) {
+        let (from, to) = (
+            (from.0 * self.scale, from.1 * self.scale),
+            (to.0 * self.scale, to.1 * self.scale),
+        );
+        // a dot per step along the longer axis
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil() as usize;
+        for i in 0..=steps {
+            let t = if steps == 0 {
+                0.0
+            } else {
+                i as f32 / steps as f32
+            };
+            self.dot(
+                (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t),
+                ink,
+            );
+        }
+    }
+
+    // labels are written as they are, left to right from the turtle
+    fn text(&mut self, (x, y): Point, text: &str, color: Color) {
+        if x < 0.0 || y < 0.0 {
+            return;
+        }
+        let column = (x * self.scale) as usize / DOTS_ACROSS;
+        let row = (y * self.scale) as usize / DOTS_DOWN;
+        if row >= self.rows {
+            return;
+        }
+        for (i, c) in text.chars().enumerate() {
+            if column + i >= self.columns {
+                break;
+            }
+            let cell = &mut self.cells[row * self.columns + column + i];
+            cell.text = Some(c);
+            cell.color = Some(color);
+        }
+    }
+}
+
+// ansi escape setting the foreground to `color`
+fn ansi(color: Color, truecolor: bool) -> String {
+    let c = unsvg::COLORS[color as usize];
+    if truecolor {
+        format!("\x1b[38;2;{};{};{}m", c.red, c.green, c.blue)
+    } else {
+        // nearest entry of the 6x6x6 cube every 256 colour terminal has
+        let level = |v: u8| (v as u16 * 5 + 127) / 255;
+        let index = 16 + 36 * level(c.red) + 6 * level(c.green) + level(c.blue);
+        format!("\x1b[38;5;{index}m")
+    }
+}
+
+// the canvas in at most `columns` x `rows` characters, coloured when asked
+pub fn render(canvas: &Canvas, columns: usize, rows: usize, color: Option<bool>) -> String {
+    let (width, height) = (canvas.width.max(1) as f32, canvas.height.max(1) as f32);
+    let scale = ((columns * DOTS_ACROSS) as f32 / width).min((rows * DOTS_DOWN) as f32 / height);
+    let across = ((width * scale).ceil() as usize).div_ceil(DOTS_ACROSS);
+    let down = ((height * scale).ceil() as usize).div_ceil(DOTS_DOWN);
+    let mut grid = Grid {
+        columns: across.clamp(1, columns.max(1)),
+        rows: down.clamp(1, rows.max(1)),
+        cells: Vec::new(),
+        scale,
+    };
+    grid.cells = vec![Cell::default(); grid.columns * grid.rows];
+
+    for stroke in canvas.strokes() {
+        for segment in stroke.points.windows(2) {
+            grid.line(segment[0], segment[1], stroke.ink);
+        }
+    }
+    for shape in &canvas.shapes {
+        if let Shape::Label {
+            at, text, color, ..
+        } = shape
+        {
+            grid.text(*at, text, *color);
+        }
+    }
+
+    let mut out = String::new();
+    for row in grid.cells.chunks(grid.columns) {
+        let mut line = String::new();
+        let mut current = None;
+        for cell in row {
+            let c = match cell.text {
+                Some(c) => c,
+                None if cell.dots == 0 => ' ',
+                None => char::from_u32(0x2800 + cell.dots as u32).expect("braille"),
+            };
+            if let (Some(truecolor), Some(cell_color)) = (color, cell.color) {
+                if c != ' ' && cell.color != current {
+                    current = cell.color;
+                    line.push_str(&ansi(cell_color, truecolor));
+                }
+            }
+            line.push(c);
+        }
+        let mut line = line.trim_end().to_string();
+        if current.is_some() {
+            line.push_str("\x1b[0m");
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+// terminal size in characters, from the terminal itself or COLUMNS and LINES
+fn terminal() -> (usize, usize) {
+    if let Some((terminal_size::Width(w), terminal_size::Height(h))) =
+        terminal_size::terminal_size()
+    {
+        return (w as usize, h as usize);
+    }
+    let env = |name: &str, default: usize| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    (env("COLUMNS", 80), env("LINES", 24))
+}
+
+// colours when writing to a terminal that does not ask for none, or
+// anywhere with CLICOLOR_FORCE; true colour when it says it has it,
+// 256 colours otherwise
+fn colors() -> Option<bool> {
+    let forced = std::env::var("CLICOLOR_FORCE").is_ok_and(|v| !v.is_empty() && v != "0");
+    if !(forced || std::io::stdout().is_terminal()) || std::env::var_os("NO_COLOR").is_some() {
+        return None;
+    }
+    let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+    Some(colorterm == "truecolor" || colorterm == "24bit")
+}
+
+// the canvas sized to the terminal, one row left for the prompt
+pub fn show(canvas: &Canvas) -> String {
+    let (columns, rows) = terminal();
+    render(canvas, columns, rows.saturating_sub(1).max(1), colors())
+}
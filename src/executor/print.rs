@@ -0,0 +1,264 @@
+use super::{
+    canvas::{number, Canvas, Ink, Shape},
+    image::ImageOptions,
+    Color, Direction, Pixel, Point,
+};
+
+// pdf and postscript share one imaging model, only the operators differ
+#[derive(Clone, Copy)]
+enum Dialect {
+    Pdf,
+    PostScript,
+}
+
+// the built-in fonts every pdf and postscript reader has, so none is embedded
+const FONTS: [&str; 3] = ["Helvetica", "Times-Roman", "Courier"];
+
+// closest built-in font to a label's font family
+fn font(family: &str) -> usize {
+    let family = family.to_lowercase();
+    if family.contains("mono") || family.contains("courier") {
+        2
+    } else if family.contains("times") || (family.contains("serif") && !family.contains("sans")) {
+        1
+    } else {
+        0
+    }
+}
+
+// text as a string literal; the built-in fonts only cover ascii
+fn string(text: &str) -> String {
+    let mut s = String::from("(");
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                s.push('\\');
+                s.push(c);
+            }
+            ' '..='~' => s.push(c),
+            _ => s.push('?'),
+        }
+    }
+    s.push(')');
+    s
+}
+
+fn num(v: f32) -> String {
+    number(v, 3)
+}
+
+struct Page {
+    dialect: Dialect,
+    out: String,
+    // line width in effect, only written when it changes
+    width: Option<f32>,
+}
+
+impl Page {
+    fn op(&mut self, operands: &[String], pdf: &str, postscript: &str) {
+        for operand in operands {
+            self.out.push_str(operand);
+            self.out.push(' ');
+        }
+        self.out.push_str(match self.dialect {
+            Dialect::Pdf => pdf,
+            Dialect::PostScript => postscript,
+        });
+        self.out.push('\n');
+    }
+
+    fn color(&mut self, color: Color, fill: bool) {
+        let c = unsvg::COLORS[color as usize];
+        let rgb = [c.red, c.green, c.blue].map(|v| num(v as f32 / 255.0));
+        self.op(&rgb, if fill { "rg" } else { "RG" }, "setrgbcolor");
+    }
+
+    fn path(&mut self, points: &[Point], close: bool) {
+        for (i, (x, y)) in points.iter().enumerate() {
+            let operands = [num(*x), num(*y)];
+            if i == 0 {
+                self.op(&operands, "m", "moveto");
+            } else {
+                self.op(&operands, "l", "lineto");
+            }
+        }
+        if close {
+            self.op(&[], "h", "closepath");
+        }
+    }
+
+    // a line written as is, for operators only one dialect has
+    fn line(&mut self, line: &str) {
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    // `matrix` is [a b c d e f], mapping onto the canvas
+    fn transform(&mut self, matrix: [f32; 6]) {
+        let matrix = matrix.map(num).join(" ");
+        match self.dialect {
+            Dialect::Pdf => self.line(&format!("{matrix} cm")),
+            Dialect::PostScript => self.line(&format!("[{matrix}] concat")),
+        }
+    }
+
+    fn text(&mut self, at: Point, direction: Direction, text: &str, font: usize, size: Pixel) {
+        // heading 90 reads left to right, glyphs flipped back upright
+        let radian = (direction - 90.0).to_radians();
+        let (cos, sin) = (radian.cos(), radian.sin());
+        let matrix = [cos, sin, sin, -cos, at.0, at.1];
+        match self.dialect {
+            Dialect::Pdf => {
+                self.line("BT");
+                self.line(&format!("/F{} {} Tf", font + 1, num(size)));
+                self.line(&format!("{} Tm", matrix.map(num).join(" ")));
+                self.line(&format!("{} Tj", string(text)));
+                self.line("ET");
+            }
+            Dialect::PostScript => {
+                self.line("gsave");
+                self.line(&format!(
+                    "/{} findfont {} scalefont setfont",
+                    FONTS[font],
+                    num(size)
+                ));
+                self.transform(matrix);
+                self.line(&format!("0 0 moveto {} show", string(text)));
+                self.line("grestore");
+            }
+        }
+    }
+}
+
+// page size in points, a canvas pixel being one point unless scaled
+fn page_size(canvas: &Canvas, options: &ImageOptions) -> (u32, u32) {
+    options.scaled(canvas.width, canvas.height)
+}
+
+// the drawing operators for the whole canvas
+fn content(canvas: &Canvas, options: &ImageOptions, dialect: Dialect) -> String {
+    let (width, height) = page_size(canvas, options);
+    let mut page = Page {
+        dialect,
+        out: String::new(),
+        width: None,
+    };
+
+    // canvas coordinates point y down, the page's point it up
+    let (sx, sy) = (
+        width as f32 / canvas.width as f32,
+        height as f32 / canvas.height as f32,
+    );
+    page.transform([sx, 0.0, 0.0, -sy, 0.0, height as f32]);
+
+    // black background, same as the original Logo screen
+    if !options.transparent {
+        let (w, h) = (canvas.width as f32, canvas.height as f32);
+        page.color(0, true);
+        page.path(&[(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)], true);
+        page.op(&[], "f", "fill");
+    }
+
+    for stroke in canvas.strokes() {
+        let width = match stroke.ink {
+            Ink::Color(color) => {
+                page.color(color, false);
+                1.0
+            }
+            // as in svg, wider than the pen; transparent pages do not get here
+            Ink::Erase => {
+                page.color(0, false);
+                3.0
+            }
+            // paper cannot invert what is already on it
+            Ink::Reverse => continue,
+        };
+        if page.width != Some(width) {
+            page.op(&[num(width)], "w", "setlinewidth");
+            page.width = Some(width);
+        }
+        page.path(&stroke.points, false);
+        page.op(&[], "S", "stroke");
+    }
+
+    // strokes leave out the filled turtle glyphs and the labels
+    for shape in &canvas.shapes {
+        match shape {
+            Shape::Polygon { points, color } => {
+                page.color(*color, true);
+                page.path(points, true);
+                page.op(&[], "f", "fill");
+            }
+            Shape::Label {
+                at,
+                direction,
+                text,
+                font: family,
+                size,
+                color,
+            } => {
+                page.color(*color, true);
+                page.text(*at, *direction, text, font(family), *size);
+            }
+            _ => {}
+        }
+    }
+    page.out
+}
+
+// a single page pdf, uncompressed so it stays readable
+pub fn pdf(canvas: &Canvas, options: &ImageOptions) -> Vec<u8> {
+    let (width, height) = page_size(canvas, options);
+    let content = content(canvas, options, Dialect::Pdf);
+    let fonts: Vec<String> = (0..FONTS.len())
+        .map(|i| format!("/F{} {} 0 R", i + 1, i + 5))
+        .collect();
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] /Resources << /Font << {} >> >> /Contents 4 0 R >>",
+            fonts.join(" ")
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ),
+    ];
+    objects.extend(FONTS.iter().map(|name| {
+        format!("<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>")
+    }));
+
+    // the cross-reference table holds the byte offset of every object
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.push_str(&format!("{} 0 obj\n{object}\nendobj\n", i + 1));
+    }
+    let xref = out.len();
+    out.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        out.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    out.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    out.into_bytes()
+}
+
+// encapsulated postscript, a bounding box the size of the page
+pub fn eps(canvas: &Canvas, options: &ImageOptions) -> Vec<u8> {
+    let (width, height) = page_size(canvas, options);
+    let mut out = format!(
+        "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 {width} {height}\n%%Creator: rslogo\n%%LanguageLevel: 2\n%%EndComments\n"
+    );
+    out.push_str(&content(canvas, options, Dialect::PostScript));
+    out.push_str("showpage\n%%EOF\n");
+    out.into_bytes()
+}
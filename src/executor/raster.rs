@@ -0,0 +1,124 @@
+use resvg::{
+    tiny_skia, usvg,
+    usvg::{TreeParsing, TreeTextToPath},
+};
+
+use super::{canvas::Canvas, image::ImageOptions, svg};
+
+// png stores its resolution in pixels per metre
+const INCHES_PER_METRE: f32 = 39.3701;
+
+// labels are rendered with whatever fonts the system provides, generic
+// families fall back to the first installed font when their default is missing
+fn system_fonts() -> usvg::fontdb::Database {
+    let mut fontdb = usvg::fontdb::Database::new();
+    fontdb.load_system_fonts();
+
+    let fallback = fontdb
+        .faces()
+        .find_map(|face| face.families.first().map(|(name, _)| name.clone()));
+    if let Some(fallback) = fallback {
+        use usvg::fontdb::{Database, Family, Query};
+        type SetFamily = fn(&mut Database, String);
+        let generics: [(Family, SetFamily); 3] = [
+            (Family::SansSerif, Database::set_sans_serif_family::<String>),
+            (Family::Serif, Database::set_serif_family::<String>),
+            (Family::Monospace, Database::set_monospace_family::<String>),
+        ];
+        for (family, set_family) in generics {
+            let query = Query {
+                families: &[family],
+                ..Default::default()
+            };
+            if fontdb.query(&query).is_none() {
+                set_family(&mut fontdb, fallback.clone());
+            }
+        }
+    }
+    fontdb
+}
+
+// averages every `samples` x `samples` block, premultiplied so edges blend
+// with transparency correctly, and returns straight rgba rows
+fn downsample(pixmap: &tiny_skia::Pixmap, samples: u32) -> Vec<u8> {
+    let (width, height) = (pixmap.width() / samples, pixmap.height() / samples);
+    let data = pixmap.data();
+    let count = samples * samples;
+
+    let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0u32; 4];
+            for sy in 0..samples {
+                let row = (y * samples + sy) * pixmap.width();
+                for sx in 0..samples {
+                    let i = ((row + x * samples + sx) * 4) as usize;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += data[i + channel] as u32;
+                    }
+                }
+            }
+            let alpha = (sum[3] + count / 2) / count;
+            for total in &sum[..3] {
+                // back from premultiplied
+                let premultiplied = (*total + count / 2) / count;
+                let straight = match alpha {
+                    0 => 0,
+                    _ => ((premultiplied * 255 + alpha / 2) / alpha).min(255),
+                };
+                rgba.push(straight as u8);
+            }
+            rgba.push(alpha as u8);
+        }
+    }
+    rgba
+}
+
+fn encode(rgba: &[u8], width: u32, height: u32, dpi: f32) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let per_metre = (dpi * INCHES_PER_METRE).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: per_metre,
+        yppu: per_metre,
+        unit: png::Unit::Meter,
+    }));
+    let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+    writer
+        .write_image_data(rgba)
+        .map_err(std::io::Error::other)?;
+    writer.finish().map_err(std::io::Error::other)?;
+    Ok(bytes)
+}
+
+// the canvas as a png, `scale` times its size
+pub fn write(canvas: &Canvas, options: &ImageOptions) -> std::io::Result<Vec<u8>> {
+    // the svg stays at canvas size, scaling happens while rendering
+    let svg = svg::write(
+        canvas,
+        &ImageOptions {
+            scale: 1.0,
+            ..options.clone()
+        },
+    );
+    let mut usvg_options = usvg::Options::default();
+    if !options.antialias {
+        usvg_options.shape_rendering = usvg::ShapeRendering::CrispEdges;
+    }
+    let mut tree = usvg::Tree::from_str(&svg, &usvg_options).map_err(std::io::Error::other)?;
+    tree.convert_text(&system_fonts());
+
+    let (width, height) = options.scaled(canvas.width, canvas.height);
+    let samples = options.supersample;
+    let mut pixmap = tiny_skia::Pixmap::new(width * samples, height * samples)
+        .ok_or_else(|| std::io::Error::other("invalid image size"))?;
+    let zoom = options.scale * samples as f32;
+    resvg::Tree::from_usvg(&tree).render(
+        tiny_skia::Transform::from_scale(zoom, zoom),
+        &mut pixmap.as_mut(),
+    );
+
+    encode(&downsample(&pixmap, samples), width, height, options.dpi)
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+use std::io::Write;
+
+use super::{
+    canvas::{number, Shape, PRECISION},
+    turtle::TurtleShape,
+    Color, Coordinates, Degree, Direction, EdgeMode, Executor, PenMode, Pixel, Point, TurtleId,
+};
+use crate::error::LogoError;
+
+// something that changes what ends up on the canvas, points in canvas pixels
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawOp {
+    Pen(PenMode),
+    Color(Color),
+    Line {
+        from: Point,
+        to: Point,
+    },
+    Arc {
+        center: Point,
+        radius: Pixel,
+        start: Direction,
+        sweep: Degree,
+    },
+    Bezier {
+        from: Point,
+        control1: Point,
+        control2: Point,
+        to: Point,
+    },
+    Label {
+        at: Point,
+        text: String,
+    },
+    Tell(TurtleId),
+    // a shown turtle, drawn when the program ends
+    Turtle {
+        points: Vec<Point>,
+        color: Color,
+    },
+}
+
+impl DrawOp {
+    // the op for a shape on the canvas, none for groups and pauses
+    fn drawn(shape: &Shape) -> Option<DrawOp> {
+        match shape.clone() {
+            Shape::Line { from, to, .. } => Some(DrawOp::Line { from, to }),
+            Shape::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+                ..
+            } => Some(DrawOp::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+            }),
+            Shape::Bezier {
+                from,
+                control1,
+                control2,
+                to,
+                ..
+            } => Some(DrawOp::Bezier {
+                from,
+                control1,
+                control2,
+                to,
+            }),
+            Shape::Label { at, text, .. } => Some(DrawOp::Label { at, text }),
+            Shape::Polygon { points, color } => Some(DrawOp::Turtle { points, color }),
+            Shape::Group(_) | Shape::EndGroup | Shape::Wait(_) => None,
+        }
+    }
+}
+
+// one op per line, numbers rounded so logs stay stable across platforms
+impl fmt::Display for DrawOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let number = |v: f32| number(v, PRECISION);
+        let point = |(x, y): &Point| format!("{} {}", number(*x), number(*y));
+        match self {
+            DrawOp::Pen(mode) => write!(f, "pen {}", format!("{mode:?}").to_lowercase()),
+            DrawOp::Color(color) => write!(f, "color {color}"),
+            DrawOp::Line { from, to } => write!(f, "line {} {}", point(from), point(to)),
+            DrawOp::Arc {
+                center,
+                radius,
+                start,
+                sweep,
+            } => write!(
+                f,
+                "arc {} {} {} {}",
+                point(center),
+                number(*radius),
+                number(*start),
+                number(*sweep)
+            ),
+            DrawOp::Bezier {
+                from,
+                control1,
+                control2,
+                to,
+            } => write!(
+                f,
+                "bezier {} {} {} {}",
+                point(from),
+                point(control1),
+                point(control2),
+                point(to)
+            ),
+            DrawOp::Label { at, text } => write!(f, "label {} {text}", point(at)),
+            DrawOp::Tell(id) => write!(f, "tell {id}"),
+            DrawOp::Turtle { points, color } => {
+                let points: Vec<String> = points.iter().map(point).collect();
+                write!(f, "turtle {color} {}", points.join(" "))
+            }
+        }
+    }
+}
+
+// forwards every call to `inner` and keeps the drawing operations it leads
+// to, written to `out` as they happen, so runs can be compared without
+// looking at pixels
+pub struct RecordingExecutor {
+    inner: Box<dyn Executor>,
+    out: Box<dyn Write>,
+    pub(super) ops: Vec<DrawOp>,
+    // shapes of `inner` already recorded
+    drawn: usize,
+}
+
+impl RecordingExecutor {
+    pub fn new(inner: Box<dyn Executor>, out: Box<dyn Write>) -> Self {
+        RecordingExecutor {
+            inner,
+            out,
+            ops: Vec::new(),
+            drawn: 0,
+        }
+    }
+
+    fn record(&mut self, op: DrawOp) {
+        // a broken output must not stop the drawing
+        let _ = writeln!(self.out, "{op}");
+        self.ops.push(op);
+    }
+
+    // runs a call on the inner executor, recording what it drew: a move
+    // split at the edges is as many lines, even when it then failed
+    fn draw<T>(&mut self, step: impl FnOnce(&mut dyn Executor) -> T) -> T {
+        let result = step(self.inner.as_mut());
+        let ops: Vec<DrawOp> = self.inner.shapes()[self.drawn..]
+            .iter()
+            .filter_map(DrawOp::drawn)
+            .collect();
+        self.drawn = self.inner.shapes().len();
+        for op in ops {
+            self.record(op);
+        }
+        result
+    }
+
+    // runs a turtle switch, recording the pen and colour it leaves when they changed
+    fn switch<T>(&mut self, step: impl FnOnce(&mut dyn Executor) -> T) -> T {
+        let (pen, color) = (self.inner.get_pen_mode(), self.inner.get_color());
+        let result = step(self.inner.as_mut());
+        if self.inner.get_pen_mode() != pen {
+            self.record(DrawOp::Pen(self.inner.get_pen_mode()));
+        }
+        if self.inner.get_color() != color {
+            self.record(DrawOp::Color(self.inner.get_color()));
+        }
+        result
+    }
+}
+
+impl Executor for RecordingExecutor {
+    fn set_source_line(&mut self, line: usize) {
+        self.inner.set_source_line(line);
+    }
+    fn set_source(&mut self, source: &str) {
+        self.inner.set_source(source);
+    }
+
+    fn pen_up(&mut self) {
+        self.inner.pen_up();
+        self.record(DrawOp::Pen(PenMode::Up));
+    }
+    fn pen_down(&mut self) {
+        self.inner.pen_down();
+        self.record(DrawOp::Pen(PenMode::Paint));
+    }
+    fn pen_erase(&mut self) {
+        self.inner.pen_erase();
+        self.record(DrawOp::Pen(PenMode::Erase));
+    }
+    fn pen_reverse(&mut self) {
+        self.inner.pen_reverse();
+        self.record(DrawOp::Pen(PenMode::Reverse));
+    }
+
+    fn foreward(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(|inner| inner.foreward(pixel))
+    }
+    fn back(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(|inner| inner.back(pixel))
+    }
+    fn left(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(|inner| inner.left(pixel))
+    }
+    fn right(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(|inner| inner.right(pixel))
+    }
+    // nothing is drawn, the next line starts where the turtle landed
+    fn jump(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.jump(pixel)
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.inner.set_color(color);
+        self.record(DrawOp::Color(color));
+    }
+
+    fn turn(&mut self, degree: Degree) {
+        self.inner.turn(degree);
+    }
+    fn set_heading(&mut self, degree: Degree) {
+        self.inner.set_heading(degree);
+    }
+
+    fn set_x_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.set_x_coordinate(pixel)
+    }
+    fn set_y_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.set_y_coordinate(pixel)
+    }
+    fn set_position(&mut self, x: Pixel, y: Pixel) -> Result<(), LogoError> {
+        self.draw(|inner| inner.set_position(x, y))
+    }
+
+    fn set_edge_mode(&mut self, mode: EdgeMode) {
+        self.inner.set_edge_mode(mode);
+    }
+
+    fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.inner.set_coordinates(coordinates);
+    }
+    fn set_window(&mut self, min: Point, max: Point) -> Result<(), LogoError> {
+        self.inner.set_window(min, max)
+    }
+
+    fn arc(&mut self, degree: Degree, radius: Pixel) {
+        self.draw(|inner| inner.arc(degree, radius))
+    }
+    fn circle(&mut self, radius: Pixel) {
+        self.draw(|inner| inner.circle(radius))
+    }
+    fn bezier(&mut self, control1: Point, control2: Point, end: Point) -> Result<(), LogoError> {
+        self.draw(|inner| inner.bezier(control1, control2, end))
+    }
+
+    fn label(&mut self, text: &str) {
+        self.draw(|inner| inner.label(text))
+    }
+    fn set_label_font(&mut self, font: &str) {
+        self.inner.set_label_font(font);
+    }
+    fn set_label_size(&mut self, size: Pixel) {
+        self.inner.set_label_size(size);
+    }
+
+    fn tell(&mut self, id: TurtleId) {
+        self.record(DrawOp::Tell(id));
+        self.switch(|inner| inner.tell(id))
+    }
+
+    fn push_turtle(&mut self) {
+        self.inner.push_turtle();
+    }
+    fn pop_turtle(&mut self) -> Result<(), LogoError> {
+        self.switch(|inner| inner.pop_turtle())
+    }
+
+    fn show_turtle(&mut self) {
+        self.inner.show_turtle();
+    }
+    fn hide_turtle(&mut self) {
+        self.inner.hide_turtle();
+    }
+    fn set_shape(&mut self, shape: TurtleShape) {
+        self.inner.set_shape(shape);
+    }
+
+    fn get_x_coordinate(&self) -> Pixel {
+        self.inner.get_x_coordinate()
+    }
+    fn get_y_coordinate(&self) -> Pixel {
+        self.inner.get_y_coordinate()
+    }
+    fn get_heading(&self) -> Direction {
+        self.inner.get_heading()
+    }
+    fn get_color(&self) -> Color {
+        self.inner.get_color()
+    }
+    fn get_pen_mode(&self) -> PenMode {
+        self.inner.get_pen_mode()
+    }
+    fn who(&self) -> TurtleId {
+        self.inner.who()
+    }
+    fn is_shown(&self) -> bool {
+        self.inner.is_shown()
+    }
+    fn shapes(&self) -> &[Shape] {
+        self.inner.shapes()
+    }
+
+    fn towards(&self, x: Pixel, y: Pixel) -> Direction {
+        self.inner.towards(x, y)
+    }
+    fn distance(&self, x: Pixel, y: Pixel) -> Pixel {
+        self.inner.distance(x, y)
+    }
+
+    fn wait(&mut self, ticks: f32) {
+        self.inner.wait(ticks);
+    }
+
+    fn begin_group(&mut self, name: &str) {
+        self.inner.begin_group(name);
+    }
+    fn end_group(&mut self) {
+        self.inner.end_group();
+    }
+
+    fn mark_stopped(&mut self) {
+        self.draw(|inner| inner.mark_stopped())
+    }
+
+    // shown turtles are only drawn now
+    fn finish(&mut self) -> Result<(), LogoError> {
+        self.draw(|inner| inner.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{image::ImageOptions, screen::Screen, ImageFormat};
+
+    fn recorder() -> RecordingExecutor {
+        let screen = Screen::new(
+            200,
+            200,
+            "unused.svg".into(),
+            ImageFormat::Svg,
+            ImageOptions::default(),
+        );
+        RecordingExecutor::new(Box::new(screen), Box::new(std::io::sink()))
+    }
+
+    #[test]
+    fn wrapped_moves_are_recorded_as_drawn() {
+        let mut recorder = recorder();
+        recorder.set_edge_mode(EdgeMode::Wrap);
+        recorder.pen_down();
+        recorder.turn(90.0);
+        recorder.foreward(150.0).expect("wrapped move");
+        // headings leave float noise, compare as the log shows them
+        let ops: Vec<String> = recorder.ops.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            ops,
+            ["pen paint", "line 100 100 200 100", "line 0 100 50 100"]
+        );
+    }
+
+    #[test]
+    fn popped_pen_and_colour_are_recorded() {
+        let mut recorder = recorder();
+        recorder.push_turtle();
+        recorder.pen_down();
+        recorder.set_color(4);
+        recorder.pop_turtle().expect("saved turtle");
+        assert_eq!(
+            recorder.ops[2..],
+            [DrawOp::Pen(PenMode::Up), DrawOp::Color(7)]
+        );
+    }
+}
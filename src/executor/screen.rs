@@ -0,0 +1,460 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{
+    animation,
+    canvas::{Canvas, Ink, Shape},
+    image::{suffixed, write_atomic, ImageFormat, ImageOptions, Placement},
+    optimize::optimize,
+    preview,
+    turtle::{Turtle, TurtleShape},
+    world::World,
+    Color, Coordinates, Degree, Direction, EdgeMode, Executor, PenMode, Pixel, Point, TurtleId,
+};
+use crate::error::LogoError;
+
+// red
+const STOPPED_COLOR: Color = 4;
+
+// edges a wrapped move is split at before the rest of it is skipped
+const MAX_CROSSINGS: usize = 10_000;
+
+// every turtle draws on the same canvas, commands go to the active one
+pub struct Screen {
+    width: u32,
+    height: u32,
+
+    turtles: BTreeMap<TurtleId, Turtle>,
+    active: TurtleId,
+    // PUSHTURTLE states, kept apart for each turtle
+    saved: BTreeMap<TurtleId, Vec<Turtle>>,
+
+    edge_mode: EdgeMode,
+    // turtles keep canvas pixels, the program sees world units
+    world: World,
+
+    path: PathBuf,
+    format: ImageFormat,
+    options: ImageOptions,
+    canvas: Canvas,
+}
+
+impl Screen {
+    pub fn new(
+        width: u32,
+        height: u32,
+        path: PathBuf,
+        format: ImageFormat,
+        options: ImageOptions,
+    ) -> Self {
+        let mut turtles = BTreeMap::new();
+        turtles.insert(0, Turtle::new(width, height));
+        Screen {
+            width,
+            height,
+            turtles,
+            active: 0,
+            saved: BTreeMap::new(),
+            edge_mode: EdgeMode::Window,
+            world: World::new(width, height),
+            path,
+            format,
+            options,
+            canvas: Canvas::new(width, height),
+        }
+    }
+
+    // encodes `canvas` in the image format and writes it to `path`
+    fn save(&self, canvas: &Canvas, path: &Path) -> Result<(), LogoError> {
+        let save_failed =
+            |e: std::io::Error| LogoError::SaveFailed(path.display().to_string(), e.to_string());
+        let bytes = self
+            .format
+            .encode(canvas, &self.options)
+            .map_err(save_failed)?;
+        write_atomic(path, &bytes).map_err(save_failed)
+    }
+
+    // active turtle position in world units
+    fn position(&self) -> Point {
+        let turtle = self.turtle();
+        self.world.to_world((turtle.x, turtle.y))
+    }
+
+    fn turtle(&self) -> &Turtle {
+        &self.turtles[&self.active]
+    }
+
+    fn turtle_mut(&mut self) -> &mut Turtle {
+        self.turtles.get_mut(&self.active).expect("active turtle")
+    }
+
+    fn draw(&mut self, offset: Degree, pixel: Pixel) -> Result<(), LogoError> {
+        let turtle = self.turtle();
+        let to = turtle.destination(turtle.direction + offset, pixel * self.world.scale());
+        self.move_to(to)
+    }
+
+    fn inside(&self, (x, y): Point) -> bool {
+        (0.0..=self.width as f32).contains(&x) && (0.0..=self.height as f32).contains(&y)
+    }
+
+    fn fence(&self, to: Point) -> Result<(), LogoError> {
+        if self.edge_mode == EdgeMode::Fence && !self.inside(to) {
+            // reported in the coordinates the program uses
+            let from = self.position();
+            let to = self.world.to_world(to);
+            let (min, max) = self.world.bounds();
+            return Err(LogoError::OutOfBounds(
+                from.0, from.1, to.0, to.1, min.0, min.1, max.0, max.1,
+            ));
+        }
+        Ok(())
+    }
+
+    // moves the active turtle without drawing
+    fn jump_to(&mut self, to: Point) -> Result<(), LogoError> {
+        self.fence(to)?;
+        let (width, height) = (self.width as f32, self.height as f32);
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        let turtle = self.turtle_mut();
+        (turtle.x, turtle.y) = if wrap {
+            (to.0.rem_euclid(width), to.1.rem_euclid(height))
+        } else {
+            to
+        };
+        Ok(())
+    }
+
+    // moves the active turtle in a straight line, drawing when the pen is down
+    fn move_to(&mut self, to: Point) -> Result<(), LogoError> {
+        self.fence(to)?;
+        let (width, height) = (self.width as f32, self.height as f32);
+        let wrap = self.edge_mode == EdgeMode::Wrap;
+        let turtle = self.turtles.get_mut(&self.active).expect("active turtle");
+        if !wrap {
+            turtle.line_to(&mut self.canvas, to);
+            return Ok(());
+        }
+
+        // split the move at every edge it crosses and carry on from the opposite edge
+        let (mut dx, mut dy) = (to.0 - turtle.x, to.1 - turtle.y);
+        let end = (to.0.rem_euclid(width), to.1.rem_euclid(height));
+        if turtle.ink().is_none() {
+            (turtle.x, turtle.y) = end;
+            return Ok(());
+        }
+        (turtle.x, turtle.y) = (turtle.x.rem_euclid(width), turtle.y.rem_euclid(height));
+        for _ in 0..MAX_CROSSINGS {
+            // fraction of the remaining move done when an edge is reached
+            let until = |from: Pixel, delta: Pixel, size: Pixel| {
+                if delta > 0.0 {
+                    (size - from) / delta
+                } else if delta < 0.0 {
+                    -from / delta
+                } else {
+                    f32::INFINITY
+                }
+            };
+            let tx = until(turtle.x, dx, width);
+            let ty = until(turtle.y, dy, height);
+            let t = tx.min(ty);
+            if t >= 1.0 {
+                turtle.line_to(&mut self.canvas, (turtle.x + dx, turtle.y + dy));
+                return Ok(());
+            }
+
+            if t > 0.0 {
+                turtle.line_to(&mut self.canvas, (turtle.x + dx * t, turtle.y + dy * t));
+            }
+            (dx, dy) = (dx * (1.0 - t), dy * (1.0 - t));
+            if tx <= ty {
+                turtle.x = if dx > 0.0 { 0.0 } else { width };
+            }
+            if ty <= tx {
+                turtle.y = if dy > 0.0 { 0.0 } else { height };
+            }
+        }
+        // the lines cover the canvas long before this, only the turtle has
+        // further to go
+        (turtle.x, turtle.y) = end;
+        Ok(())
+    }
+}
+
+impl Executor for Screen {
+    // the canvas keeps the line for what it draws, and to show where a
+    // failed program stopped
+    fn set_source_line(&mut self, line: usize) {
+        self.canvas.set_line(line);
+    }
+
+    fn set_source(&mut self, source: &str) {
+        self.canvas.source = source.to_string();
+    }
+
+    fn pen_up(&mut self) {
+        self.turtle_mut().pen = PenMode::Up;
+    }
+    fn pen_down(&mut self) {
+        self.turtle_mut().pen = PenMode::Paint;
+    }
+    fn pen_erase(&mut self) {
+        self.turtle_mut().pen = PenMode::Erase;
+    }
+    fn pen_reverse(&mut self) {
+        self.turtle_mut().pen = PenMode::Reverse;
+    }
+
+    fn foreward(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(0.0, pixel)
+    }
+
+    fn back(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(180.0, pixel)
+    }
+
+    fn left(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(270.0, pixel)
+    }
+
+    fn right(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.draw(90.0, pixel)
+    }
+    fn jump(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        let turtle = self.turtle();
+        let to = turtle.destination(turtle.direction, pixel * self.world.scale());
+        self.jump_to(to)
+    }
+
+    fn set_color(&mut self, color: Color) {
+        // @todo error handle
+        debug_assert!(color <= 15);
+        self.turtle_mut().color_idx = color;
+    }
+
+    fn turn(&mut self, degree: Degree) {
+        self.turtle_mut().direction += degree;
+    }
+
+    fn set_heading(&mut self, degree: Degree) {
+        self.turtle_mut().direction = degree;
+    }
+
+    fn set_x_coordinate(&mut self, coordinate: Pixel) -> Result<(), LogoError> {
+        let (_, y) = self.position();
+        self.jump_to(self.world.to_canvas((coordinate, y)))
+    }
+    fn set_y_coordinate(&mut self, coordinate: Pixel) -> Result<(), LogoError> {
+        let (x, _) = self.position();
+        self.jump_to(self.world.to_canvas((x, coordinate)))
+    }
+    fn set_position(&mut self, x: Pixel, y: Pixel) -> Result<(), LogoError> {
+        self.move_to(self.world.to_canvas((x, y)))
+    }
+
+    fn set_edge_mode(&mut self, mode: EdgeMode) {
+        self.edge_mode = mode;
+    }
+
+    fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.world.set_coordinates(coordinates);
+    }
+    fn set_window(&mut self, min: Point, max: Point) -> Result<(), LogoError> {
+        // also refuses NaN
+        if !(min.0 < max.0 && min.1 < max.1) {
+            return Err(LogoError::InvalidWindow(min.0, min.1, max.0, max.1));
+        }
+        self.world.set_window(min, max);
+        Ok(())
+    }
+
+    fn arc(&mut self, degree: Degree, radius: Pixel) {
+        let radius = radius * self.world.scale();
+        let turtle = &self.turtles[&self.active];
+        if let Some(ink) = turtle.ink() {
+            self.canvas
+                .arc((turtle.x, turtle.y), radius, turtle.direction, degree, ink);
+        }
+    }
+    fn circle(&mut self, radius: Pixel) {
+        self.arc(360.0, radius);
+    }
+    fn bezier(&mut self, control1: Point, control2: Point, end: Point) -> Result<(), LogoError> {
+        let [control1, control2, end] = [control1, control2, end].map(|p| self.world.to_canvas(p));
+        // the curve itself is not split, only where the turtle ends up follows the edge mode
+        self.fence(end)?;
+        let turtle = &self.turtles[&self.active];
+        if let Some(ink) = turtle.ink() {
+            self.canvas
+                .bezier((turtle.x, turtle.y), control1, control2, end, ink);
+        }
+        self.jump_to(end)
+    }
+
+    fn label(&mut self, text: &str) {
+        let turtle = &self.turtles[&self.active];
+        // text shows whether the pen is up or down
+        self.canvas.label(
+            (turtle.x, turtle.y),
+            turtle.direction,
+            text,
+            &turtle.label_font,
+            turtle.label_size,
+            turtle.color_idx,
+        );
+    }
+    fn set_label_font(&mut self, font: &str) {
+        self.turtle_mut().label_font = font.to_string();
+    }
+    fn set_label_size(&mut self, size: Pixel) {
+        // in world units like every other length, kept in pixels
+        let size = size * self.world.scale();
+        self.turtle_mut().label_size = size;
+    }
+
+    fn tell(&mut self, id: TurtleId) {
+        // a new turtle starts from the centre like the first one did
+        let (width, height) = (self.width, self.height);
+        self.turtles
+            .entry(id)
+            .or_insert_with(|| Turtle::new(width, height));
+        self.active = id;
+    }
+
+    fn push_turtle(&mut self) {
+        let turtle = self.turtle().clone();
+        self.saved.entry(self.active).or_default().push(turtle);
+    }
+    fn pop_turtle(&mut self) -> Result<(), LogoError> {
+        let turtle = self
+            .saved
+            .get_mut(&self.active)
+            .and_then(|saved| saved.pop())
+            .ok_or(LogoError::EmptyTurtleStack)?;
+        *self.turtle_mut() = turtle;
+        Ok(())
+    }
+
+    fn show_turtle(&mut self) {
+        self.turtle_mut().visible = true;
+    }
+    fn hide_turtle(&mut self) {
+        self.turtle_mut().visible = false;
+    }
+    fn set_shape(&mut self, shape: TurtleShape) {
+        self.turtle_mut().shape = shape;
+    }
+
+    fn get_x_coordinate(&self) -> Pixel {
+        self.position().0
+    }
+    fn get_y_coordinate(&self) -> Pixel {
+        self.position().1
+    }
+    fn get_heading(&self) -> Direction {
+        self.turtle().direction
+    }
+    fn get_color(&self) -> Color {
+        self.turtle().color_idx
+    }
+    fn get_pen_mode(&self) -> PenMode {
+        self.turtle().pen
+    }
+    fn who(&self) -> TurtleId {
+        self.active
+    }
+    fn is_shown(&self) -> bool {
+        self.turtle().visible
+    }
+    fn shapes(&self) -> &[Shape] {
+        &self.canvas.shapes
+    }
+
+    fn towards(&self, x: Pixel, y: Pixel) -> Direction {
+        let turtle = self.turtle();
+        let (x, y) = self.world.to_canvas((x, y));
+        // y grows downwards on the canvas, heading 0 points up
+        let degree = (x - turtle.x).atan2(turtle.y - y).to_degrees();
+        if degree < 0.0 {
+            degree + 360.0
+        } else {
+            degree
+        }
+    }
+    fn distance(&self, x: Pixel, y: Pixel) -> Pixel {
+        let (tx, ty) = self.position();
+        (x - tx).hypot(y - ty)
+    }
+
+    fn wait(&mut self, ticks: f32) {
+        self.canvas.wait(ticks.max(0.0) / 60.0);
+    }
+
+    fn begin_group(&mut self, name: &str) {
+        self.canvas.group(name);
+    }
+    fn end_group(&mut self) {
+        self.canvas.end_group();
+    }
+
+    fn mark_stopped(&mut self) {
+        // red cross over the turtle, with the line it failed on
+        let (x, y) = (self.turtle().x, self.turtle().y);
+        let ink = Ink::Color(STOPPED_COLOR);
+        self.canvas
+            .line((x - 5.0, y - 5.0), (x + 5.0, y + 5.0), ink);
+        self.canvas
+            .line((x - 5.0, y + 5.0), (x + 5.0, y - 5.0), ink);
+        self.canvas.label(
+            (x + 8.0, y - 8.0),
+            90.0,
+            &format!("line {}", self.canvas.line),
+            "sans-serif",
+            12.0,
+            STOPPED_COLOR,
+        );
+    }
+
+    fn finish(&mut self) -> Result<(), LogoError> {
+        // turtles are drawn last so they sit on top of the drawing
+        for turtle in self.turtles.values().filter(|turtle| turtle.visible) {
+            self.canvas.polygon(turtle.glyph(), turtle.color_idx);
+        }
+
+        let mut canvas = Cow::Borrowed(&self.canvas);
+        if self.options.placement != Placement::AsDrawn {
+            canvas = Cow::Owned(canvas.placed(self.options.placement, self.options.margin));
+        }
+        if let Some(tolerance) = self.options.optimize {
+            canvas = Cow::Owned(optimize(&canvas, tolerance));
+        }
+        let canvas = canvas.as_ref();
+        if self.options.preview {
+            print!("{}", preview::show(canvas));
+        }
+        if !self.options.save {
+            return Ok(());
+        }
+        // erasing paints the background, there is none to paint on a
+        // transparent image; plotters leave erasing out anyway
+        let plotted = matches!(self.format, ImageFormat::GCode | ImageFormat::Hpgl);
+        if self.options.transparent && !plotted && canvas.erases() {
+            return Err(LogoError::EraseOnTransparent);
+        }
+        if self.options.animation.is_some() {
+            return animation::write(canvas, self.format, &self.path, &self.options).map_err(|e| {
+                LogoError::SaveFailed(self.path.display().to_string(), e.to_string())
+            });
+        }
+        if !self.options.split_colors {
+            return self.save(canvas, &self.path);
+        }
+        for (color, layer) in canvas.layers() {
+            // `drawing-4.svg` for pen colour 4
+            self.save(&layer, &suffixed(&self.path, &color.to_string()))?;
+        }
+        Ok(())
+    }
+}
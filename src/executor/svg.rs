@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+
+use super::{
+    canvas::{number, polar, Canvas, Ink, Shape},
+    image::ImageOptions,
+    Color, Point,
+};
+
+pub(super) const BACKGROUND: &str = "#000000";
+
+// text for xml content and attributes, also used for the html page
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(super) fn hex(color: Color) -> String {
+    let c = unsvg::COLORS[color as usize];
+    format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue)
+}
+
+fn stroke(ink: Ink) -> String {
+    match ink {
+        Ink::Color(color) => format!("stroke=\"{}\"", hex(color)),
+        // wider than the pen so antialiased edges go as well; never used on a
+        // transparent background, which has nothing to paint with
+        Ink::Erase => format!("stroke=\"{BACKGROUND}\" stroke-width=\"3\""),
+        // white difference flips every channel, without antialiasing drawing twice restores
+        Ink::Reverse => {
+            "stroke=\"#ffffff\" style=\"mix-blend-mode:difference\" shape-rendering=\"crispEdges\""
+                .to_string()
+        }
+    }
+}
+
+// xml ids cannot hold spaces and must not start with a digit
+fn group_id(name: &str, seen: &mut HashMap<String, usize>) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !id.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        id.insert(0, '_');
+    }
+    let count = seen.entry(id.clone()).or_insert(0);
+    *count += 1;
+    format!("{id}-{count}")
+}
+
+struct Writer<'a> {
+    options: &'a ImageOptions,
+    svg: String,
+    // pen-down segments waiting to be written as one element
+    ink: Option<Ink>,
+    runs: Vec<Vec<Point>>,
+    // group ids handed out so far
+    seen: HashMap<String, usize>,
+    // groups not closed yet, left open by a program that stopped in a procedure
+    open: usize,
+}
+
+impl<'a> Writer<'a> {
+    // the document up to and including the background
+    fn new(canvas: &Canvas, options: &'a ImageOptions) -> Self {
+        // the viewBox keeps canvas coordinates whatever size the image is shown at
+        let (width, height) = options.scaled(canvas.width, canvas.height);
+        let mut writer = Writer {
+            options,
+            svg: format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\"{} width=\"{}\" height=\"{}\" viewBox=\"0 0 {w} {h}\">\n",
+                if options.layers {
+                    " xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\""
+                } else {
+                    ""
+                },
+                width,
+                height,
+                w = canvas.width,
+                h = canvas.height
+            ),
+            ink: None,
+            runs: Vec::new(),
+            seen: HashMap::new(),
+            open: 0,
+        };
+        // black background, same as the original Logo screen
+        if !options.transparent {
+            writer.svg.push_str(&format!(
+                "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{BACKGROUND}\"/>\n",
+                canvas.width, canvas.height
+            ));
+        }
+        writer
+    }
+
+    fn close_groups(&mut self) {
+        for _ in 0..self.open {
+            self.push("</g>\n".to_string());
+        }
+        self.open = 0;
+    }
+
+    fn finish(mut self) -> String {
+        self.close_groups();
+        self.flush();
+        self.svg.push_str("</svg>\n");
+        self.svg
+    }
+
+    fn number(&self, v: f32) -> String {
+        number(v, self.options.precision)
+    }
+
+    fn point(&self, (x, y): Point) -> String {
+        format!("{} {}", self.number(x), self.number(y))
+    }
+
+    // segments of the same ink are gathered, joined into one run when they touch
+    fn line(&mut self, from: Point, to: Point, ink: Ink) {
+        // reversing segments only cancel out when drawn as separate elements
+        if self.ink != Some(ink) || ink == Ink::Reverse {
+            self.flush();
+            self.ink = Some(ink);
+        }
+        // compared as written, so points equal at the output precision join up
+        let last = self.runs.last().and_then(|run| run.last());
+        if last.map(|p| self.point(*p)) == Some(self.point(from)) {
+            self.runs.last_mut().expect("last run").push(to);
+        } else {
+            self.runs.push(vec![from, to]);
+        }
+    }
+
+    fn flush(&mut self) {
+        let Some(ink) = self.ink.take() else {
+            return;
+        };
+        let runs = std::mem::take(&mut self.runs);
+        if let [run] = runs.as_slice() {
+            let points: Vec<String> = run
+                .iter()
+                .map(|(x, y)| format!("{},{}", self.number(*x), self.number(*y)))
+                .collect();
+            self.svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" {}/>\n",
+                points.join(" "),
+                stroke(ink)
+            ));
+        } else {
+            let mut d = Vec::new();
+            for run in &runs {
+                d.push(format!("M {}", self.point(run[0])));
+                d.extend(run[1..].iter().map(|p| format!("L {}", self.point(*p))));
+            }
+            self.svg.push_str(&format!(
+                "<path d=\"{}\" fill=\"none\" {}/>\n",
+                d.join(" "),
+                stroke(ink)
+            ));
+        }
+    }
+
+    fn push(&mut self, element: String) {
+        self.flush();
+        self.svg.push_str(&element);
+    }
+
+    fn shapes(&mut self, shapes: &[Shape]) {
+        for shape in shapes {
+            match shape {
+                Shape::Line { from, to, ink } => self.line(*from, *to, *ink),
+                Shape::Arc {
+                    center,
+                    radius,
+                    start,
+                    sweep,
+                    ink,
+                } => {
+                    let element = if sweep.abs() >= 360.0 {
+                        format!(
+                            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" {}/>\n",
+                            self.number(center.0),
+                            self.number(center.1),
+                            self.number(*radius),
+                            stroke(*ink)
+                        )
+                    } else {
+                        let from = polar(*center, *radius, *start);
+                        let to = polar(*center, *radius, start + sweep);
+                        // positive sweep is clockwise, which is sweep-flag 1 with y pointing down
+                        format!(
+                            "<path d=\"M {} A {r} {r} 0 {} {} {}\" fill=\"none\" {}/>\n",
+                            self.point(from),
+                            (sweep.abs() > 180.0) as u8,
+                            (*sweep > 0.0) as u8,
+                            self.point(to),
+                            stroke(*ink),
+                            r = self.number(*radius),
+                        )
+                    };
+                    self.push(element);
+                }
+                Shape::Bezier {
+                    from,
+                    control1,
+                    control2,
+                    to,
+                    ink,
+                } => {
+                    let element = format!(
+                        "<path d=\"M {} C {} {} {}\" fill=\"none\" {}/>\n",
+                        self.point(*from),
+                        self.point(*control1),
+                        self.point(*control2),
+                        self.point(*to),
+                        stroke(*ink)
+                    );
+                    self.push(element);
+                }
+                Shape::Polygon { points, color } => {
+                    let points: Vec<String> = points
+                        .iter()
+                        .map(|(x, y)| format!("{},{}", self.number(*x), self.number(*y)))
+                        .collect();
+                    let element = format!(
+                        "<polygon points=\"{}\" fill=\"{}\"/>\n",
+                        points.join(" "),
+                        hex(*color)
+                    );
+                    self.push(element);
+                }
+                Shape::Label {
+                    at,
+                    direction,
+                    text,
+                    font,
+                    size,
+                    color,
+                } => {
+                    let (x, y) = (self.number(at.0), self.number(at.1));
+                    // heading 90 reads left to right
+                    let element = format!(
+                        "<text x=\"{x}\" y=\"{y}\" font-family=\"{}\" font-size=\"{}\" fill=\"{}\" transform=\"rotate({} {x} {y})\">{}</text>\n",
+                        escape(font),
+                        self.number(*size),
+                        hex(*color),
+                        self.number(direction - 90.0),
+                        escape(text),
+                    );
+                    self.push(element);
+                }
+                Shape::Group(name) if self.options.groups => {
+                    let element = format!("<g id=\"{}\">\n", group_id(name, &mut self.seen));
+                    self.push(element);
+                    self.open += 1;
+                }
+                Shape::EndGroup if self.options.groups => {
+                    self.push("</g>\n".to_string());
+                    self.open = self.open.saturating_sub(1);
+                }
+                // without groups, paths merge across procedure calls
+                Shape::Group(_) | Shape::EndGroup => {}
+                // only animations have a timeline
+                Shape::Wait(_) => {}
+            }
+        }
+    }
+}
+
+// the canvas as an svg document
+pub fn write(canvas: &Canvas, options: &ImageOptions) -> String {
+    let mut writer = Writer::new(canvas, options);
+    if options.layers {
+        // one inkscape layer per pen, in the order the pens were first used
+        for (color, layer) in canvas.layers() {
+            writer.push(format!(
+                "<g inkscape:groupmode=\"layer\" inkscape:label=\"pen {color} {}\" id=\"pen-{color}\">\n",
+                hex(color)
+            ));
+            writer.shapes(&layer.shapes);
+            writer.close_groups();
+            writer.push("</g>\n".to_string());
+        }
+    } else {
+        writer.shapes(&canvas.shapes);
+    }
+    writer.finish()
+}
+
+// the markup of each shape on its own, empty for those that draw nothing,
+// for players that show the drawing a step at a time
+pub fn elements(canvas: &Canvas, options: &ImageOptions) -> Vec<String> {
+    // groups would open in one step and close in another
+    let options = ImageOptions {
+        groups: false,
+        ..options.clone()
+    };
+    let mut writer = Writer::new(canvas, &options);
+    canvas
+        .shapes
+        .iter()
+        .map(|shape| {
+            writer.svg.clear();
+            writer.shapes(std::slice::from_ref(shape));
+            writer.flush();
+            writer.svg.clone()
+        })
+        .collect()
+}
+
+// the canvas drawn frame by frame, frame k adding shapes up to `ends[k]`
+// and appearing `fps` times a second; smil needs no script to play
+pub fn animate(canvas: &Canvas, options: &ImageOptions, ends: &[usize], fps: f32) -> String {
+    let mut writer = Writer::new(canvas, options);
+    let mut start = 0;
+    for (frame, end) in ends.iter().enumerate() {
+        let shapes = &canvas.shapes[start..*end];
+        start = *end;
+        if shapes.is_empty() {
+            continue;
+        }
+        writer.push(format!(
+            "<g visibility=\"hidden\">\n<set attributeName=\"visibility\" to=\"visible\" begin=\"{}s\" fill=\"freeze\"/>\n",
+            number(frame as f32 / fps, 3)
+        ));
+        writer.shapes(shapes);
+        writer.push("</g>\n".to_string());
+    }
+    writer.finish()
+}
@@ -0,0 +1,338 @@
+use std::io::Write;
+
+use super::{
+    canvas::Shape, turtle::TurtleShape, Color, Coordinates, Degree, Direction, EdgeMode, Executor,
+    PenMode, Pixel, Point, TurtleId,
+};
+use crate::error::LogoError;
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum TraceFormat {
+    // one readable line per command
+    Text,
+    // one json object per line
+    Json,
+}
+
+// argument of a traced command
+enum Value {
+    Number(f32),
+    Word(String),
+}
+
+fn json_number(v: f32) -> String {
+    // json has no NaN or infinity
+    if v.is_finite() {
+        format!("{v}")
+    } else {
+        "null".to_string()
+    }
+}
+
+pub(super) fn json_string(text: &str) -> String {
+    let mut s = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+// forwards every call to `inner`, reporting each command that changes the
+// drawing or a turtle together with the state it leaves the turtle in
+pub struct TraceExecutor {
+    inner: Box<dyn Executor>,
+    format: TraceFormat,
+    out: Box<dyn Write>,
+    line: usize,
+}
+
+impl TraceExecutor {
+    pub fn new(inner: Box<dyn Executor>, format: TraceFormat, out: Box<dyn Write>) -> Self {
+        TraceExecutor {
+            inner,
+            format,
+            out,
+            line: 0,
+        }
+    }
+
+    fn event(&mut self, command: &str, args: &[Value]) {
+        let (x, y) = (self.inner.get_x_coordinate(), self.inner.get_y_coordinate());
+        let heading = self.inner.get_heading();
+        let turtle = self.inner.who();
+
+        let text = match self.format {
+            TraceFormat::Text => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Value::Number(v) => format!("{v}"),
+                        Value::Word(w) => format!("\"{w}"),
+                    })
+                    .collect();
+                let mut call = command.to_string();
+                if !args.is_empty() {
+                    call = format!("{call} {}", args.join(" "));
+                }
+                format!(
+                    "line {}: {call} -> turtle {turtle} at ({x}, {y}) heading {heading}",
+                    self.line
+                )
+            }
+            TraceFormat::Json => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Value::Number(v) => json_number(*v),
+                        Value::Word(w) => json_string(w),
+                    })
+                    .collect();
+                format!(
+                    "{{\"line\":{},\"command\":{},\"args\":[{}],\"turtle\":{turtle},\"x\":{},\"y\":{},\"heading\":{}}}",
+                    self.line,
+                    json_string(command),
+                    args.join(","),
+                    json_number(x),
+                    json_number(y),
+                    json_number(heading)
+                )
+            }
+        };
+        // a broken trace output must not stop the drawing
+        let _ = writeln!(self.out, "{text}");
+    }
+}
+
+impl Executor for TraceExecutor {
+    fn set_source_line(&mut self, line: usize) {
+        self.line = line;
+        self.inner.set_source_line(line);
+    }
+    fn set_source(&mut self, source: &str) {
+        self.inner.set_source(source);
+    }
+
+    fn pen_up(&mut self) {
+        self.inner.pen_up();
+        self.event("PENUP", &[]);
+    }
+    fn pen_down(&mut self) {
+        self.inner.pen_down();
+        self.event("PENDOWN", &[]);
+    }
+    fn pen_erase(&mut self) {
+        self.inner.pen_erase();
+        self.event("PENERASE", &[]);
+    }
+    fn pen_reverse(&mut self) {
+        self.inner.pen_reverse();
+        self.event("PENREVERSE", &[]);
+    }
+
+    fn foreward(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.foreward(pixel)?;
+        self.event("FORWARD", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn back(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.back(pixel)?;
+        self.event("BACK", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn left(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.left(pixel)?;
+        self.event("LEFT", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn jump(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.jump(pixel)?;
+        self.event("JUMP", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn right(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.right(pixel)?;
+        self.event("RIGHT", &[Value::Number(pixel)]);
+        Ok(())
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.inner.set_color(color);
+        self.event("SETPENCOLOR", &[Value::Number(color as f32)]);
+    }
+
+    fn turn(&mut self, degree: Degree) {
+        self.inner.turn(degree);
+        self.event("TURN", &[Value::Number(degree)]);
+    }
+    fn set_heading(&mut self, degree: Degree) {
+        self.inner.set_heading(degree);
+        self.event("SETHEADING", &[Value::Number(degree)]);
+    }
+
+    fn set_x_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.set_x_coordinate(pixel)?;
+        self.event("SETX", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn set_y_coordinate(&mut self, pixel: Pixel) -> Result<(), LogoError> {
+        self.inner.set_y_coordinate(pixel)?;
+        self.event("SETY", &[Value::Number(pixel)]);
+        Ok(())
+    }
+    fn set_position(&mut self, x: Pixel, y: Pixel) -> Result<(), LogoError> {
+        self.inner.set_position(x, y)?;
+        self.event("SETXY", &[Value::Number(x), Value::Number(y)]);
+        Ok(())
+    }
+
+    fn set_edge_mode(&mut self, mode: EdgeMode) {
+        self.inner.set_edge_mode(mode);
+        let command = match mode {
+            EdgeMode::Wrap => "WRAP",
+            EdgeMode::Fence => "FENCE",
+            EdgeMode::Window => "WINDOW",
+        };
+        self.event(command, &[]);
+    }
+
+    // a setting of the run rather than a command, the trace header has it
+    fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.inner.set_coordinates(coordinates);
+    }
+    fn set_window(&mut self, min: Point, max: Point) -> Result<(), LogoError> {
+        self.inner.set_window(min, max)?;
+        let args = [min.0, min.1, max.0, max.1].map(Value::Number);
+        self.event("SETWINDOW", &args);
+        Ok(())
+    }
+
+    fn arc(&mut self, degree: Degree, radius: Pixel) {
+        self.inner.arc(degree, radius);
+        self.event("ARC", &[Value::Number(degree), Value::Number(radius)]);
+    }
+    fn circle(&mut self, radius: Pixel) {
+        self.inner.circle(radius);
+        self.event("CIRCLE", &[Value::Number(radius)]);
+    }
+    fn bezier(&mut self, control1: Point, control2: Point, end: Point) -> Result<(), LogoError> {
+        self.inner.bezier(control1, control2, end)?;
+        let args = [control1, control2, end]
+            .into_iter()
+            .flat_map(|(x, y)| [Value::Number(x), Value::Number(y)])
+            .collect::<Vec<_>>();
+        self.event("BEZIER", &args);
+        Ok(())
+    }
+
+    fn label(&mut self, text: &str) {
+        self.inner.label(text);
+        self.event("LABEL", &[Value::Word(text.to_string())]);
+    }
+    fn set_label_font(&mut self, font: &str) {
+        self.inner.set_label_font(font);
+        self.event("SETLABELFONT", &[Value::Word(font.to_string())]);
+    }
+    fn set_label_size(&mut self, size: Pixel) {
+        self.inner.set_label_size(size);
+        self.event("SETLABELSIZE", &[Value::Number(size)]);
+    }
+
+    fn tell(&mut self, id: TurtleId) {
+        self.inner.tell(id);
+        self.event("TELL", &[Value::Number(id as f32)]);
+    }
+
+    fn push_turtle(&mut self) {
+        self.inner.push_turtle();
+        self.event("PUSHTURTLE", &[]);
+    }
+    fn pop_turtle(&mut self) -> Result<(), LogoError> {
+        self.inner.pop_turtle()?;
+        self.event("POPTURTLE", &[]);
+        Ok(())
+    }
+
+    fn show_turtle(&mut self) {
+        self.inner.show_turtle();
+        self.event("SHOWTURTLE", &[]);
+    }
+    fn hide_turtle(&mut self) {
+        self.inner.hide_turtle();
+        self.event("HIDETURTLE", &[]);
+    }
+    fn set_shape(&mut self, shape: TurtleShape) {
+        let args = match &shape {
+            TurtleShape::Triangle => vec![Value::Word("triangle".to_string())],
+            TurtleShape::Arrow => vec![Value::Word("arrow".to_string())],
+            TurtleShape::Polygon(points) => points
+                .iter()
+                .flat_map(|(x, y)| [Value::Number(*x), Value::Number(*y)])
+                .collect(),
+        };
+        self.inner.set_shape(shape);
+        self.event("SETSHAPE", &args);
+    }
+
+    fn get_x_coordinate(&self) -> Pixel {
+        self.inner.get_x_coordinate()
+    }
+    fn get_y_coordinate(&self) -> Pixel {
+        self.inner.get_y_coordinate()
+    }
+    fn get_heading(&self) -> Direction {
+        self.inner.get_heading()
+    }
+    fn get_color(&self) -> Color {
+        self.inner.get_color()
+    }
+    fn get_pen_mode(&self) -> PenMode {
+        self.inner.get_pen_mode()
+    }
+    fn who(&self) -> TurtleId {
+        self.inner.who()
+    }
+    fn is_shown(&self) -> bool {
+        self.inner.is_shown()
+    }
+    fn shapes(&self) -> &[Shape] {
+        self.inner.shapes()
+    }
+
+    fn towards(&self, x: Pixel, y: Pixel) -> Direction {
+        self.inner.towards(x, y)
+    }
+    fn distance(&self, x: Pixel, y: Pixel) -> Pixel {
+        self.inner.distance(x, y)
+    }
+
+    fn wait(&mut self, ticks: f32) {
+        self.inner.wait(ticks);
+        self.event("WAIT", &[Value::Number(ticks)]);
+    }
+
+    // procedure calls, so a replay groups the svg as the run did
+    fn begin_group(&mut self, name: &str) {
+        self.inner.begin_group(name);
+        self.event("GROUP", &[Value::Word(name.to_string())]);
+    }
+    fn end_group(&mut self) {
+        self.inner.end_group();
+        self.event("ENDGROUP", &[]);
+    }
+
+    fn mark_stopped(&mut self) {
+        self.inner.mark_stopped();
+    }
+
+    fn finish(&mut self) -> Result<(), LogoError> {
+        self.inner.finish()
+    }
+}
@@ -1,151 +1,129 @@
-use super::{Color, Degree, Direction, Executor, Pixel};
+use super::{
+    canvas::{Canvas, Ink},
+    Color, Direction, PenMode, Pixel, Point,
+};
+
+// snap to 1/256 pixel so axis aligned moves land on exact coordinates
+fn quantize(v: Pixel) -> Pixel {
+    (v * 256.0).round() / 256.0
+}
+
+// glyph drawn for a shown turtle, outlines are given facing heading 0
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurtleShape {
+    Triangle,
+    Arrow,
+    // corners around the turtle position
+    Polygon(Vec<Point>),
+}
+
+impl TurtleShape {
+    // SETSHAPE "triangle, "arrow or a [ x1 y1 x2 y2 ... ] list of corners
+    pub fn from_words(words: &str) -> Option<Self> {
+        match words {
+            "triangle" => Some(TurtleShape::Triangle),
+            "arrow" => Some(TurtleShape::Arrow),
+            _ => {
+                let mut numbers = Vec::new();
+                for word in words.split_whitespace() {
+                    numbers.push(word.trim_start_matches('"').parse::<Pixel>().ok()?);
+                }
+                if numbers.len() < 6 || numbers.len() % 2 != 0 {
+                    return None;
+                }
+                Some(TurtleShape::Polygon(
+                    numbers.chunks(2).map(|xy| (xy[0], xy[1])).collect(),
+                ))
+            }
+        }
+    }
 
-#[derive(PartialEq)]
-enum Status {
-    Up,
-    Down,
+    fn outline(&self) -> Vec<Point> {
+        match self {
+            TurtleShape::Triangle => vec![(0.0, -10.0), (6.0, 6.0), (-6.0, 6.0)],
+            TurtleShape::Arrow => vec![
+                (0.0, -10.0),
+                (7.0, 0.0),
+                (2.5, 0.0),
+                (2.5, 8.0),
+                (-2.5, 8.0),
+                (-2.5, 0.0),
+                (-7.0, 0.0),
+            ],
+            TurtleShape::Polygon(points) => points.clone(),
+        }
+    }
 }
 
+// state of one turtle, the canvas it draws on is owned by the screen
+#[derive(Clone)]
 pub struct Turtle {
-    direction: Direction,
+    pub(super) direction: Direction,
 
-    x: Pixel,
-    y: Pixel,
+    pub(super) x: Pixel,
+    pub(super) y: Pixel,
 
-    color_idx: Color,
-    status: Status,
+    pub(super) color_idx: Color,
+    pub(super) pen: PenMode,
 
-    path: std::path::PathBuf,
-    image: unsvg::Image,
+    pub(super) label_font: String,
+    pub(super) label_size: Pixel,
+
+    pub(super) visible: bool,
+    pub(super) shape: TurtleShape,
 }
 
 impl Turtle {
-    pub fn new(width: u32, height: u32, path: std::path::PathBuf) -> Self {
+    pub fn new(width: u32, height: u32) -> Self {
         Turtle {
-            direction: 0,
+            direction: 0.0,
             x: width as f32 / 2.0,
             y: height as f32 / 2.0,
             // white
             color_idx: 7,
-            status: Status::Up,
-            path,
-            image: unsvg::Image::new(width, height),
+            pen: PenMode::Up,
+            label_font: "sans-serif".to_string(),
+            label_size: 12.0,
+            // hidden unless asked for, so plain drawings stay unchanged
+            visible: false,
+            shape: TurtleShape::Triangle,
         }
     }
 
-    fn draw(&mut self, direction: Direction, pixel: Pixel) {
-        if self.status == Status::Up {
-            (self.x, self.y) = unsvg::get_end_coordinates(self.x, self.y, direction, pixel);
-        } else {
-            (self.x, self.y) = self
-                .image
-                .draw_simple_line(
-                    self.x,
-                    self.y,
-                    direction,
-                    pixel,
-                    unsvg::COLORS[self.color_idx as usize],
-                )
-                // @todo error handle
-                .expect("failed to write line");
+    // where `pixel` steps towards `direction` would take the turtle
+    pub fn destination(&self, direction: Direction, pixel: Pixel) -> Point {
+        // directions start at 0 degrees being straight up, and go clockwise
+        let radian = (direction - 90.0).to_radians();
+        (
+            quantize(self.x + radian.cos() * pixel),
+            quantize(self.y + radian.sin() * pixel),
+        )
+    }
+
+    // corners of the turtle shape at its position, turned to its heading
+    pub fn glyph(&self) -> Vec<Point> {
+        let (sin, cos) = self.direction.to_radians().sin_cos();
+        self.shape
+            .outline()
+            .into_iter()
+            .map(|(x, y)| (self.x + x * cos - y * sin, self.y + x * sin + y * cos))
+            .collect()
+    }
+
+    // how the pen marks the canvas, nothing when it is up
+    pub fn ink(&self) -> Option<Ink> {
+        match self.pen {
+            PenMode::Up => None,
+            PenMode::Paint => Some(Ink::Color(self.color_idx)),
+            PenMode::Erase => Some(Ink::Erase),
+            PenMode::Reverse => Some(Ink::Reverse),
         }
     }
-}
-
-impl Executor for Turtle {
-    fn pen_up(&mut self) {
-        println!("turtle pen_up");
-        self.status = Status::Up;
-    }
-    fn pen_down(&mut self) {
-        println!("turtle pen_down");
-        self.status = Status::Down;
-    }
-
-    fn foreward(&mut self, pixel: Pixel) {
-        println!("turtle foreward: {pixel}");
-        self.draw(self.direction + 0, pixel);
-    }
-
-    fn back(&mut self, pixel: Pixel) {
-        println!("turtle back: {pixel}");
-        self.draw(self.direction + 180, pixel);
-    }
-
-    fn left(&mut self, pixel: Pixel) {
-        println!("turtle left: {pixel}");
-        self.draw(self.direction + 270, pixel);
-    }
 
-    fn right(&mut self, pixel: Pixel) {
-        self.draw(self.direction + 90, pixel);
-    }
-
-    fn set_color(&mut self, color: Color) {
-        println!("turtle set_color: {color}");
-        // @todo error handle
-        debug_assert!(color <= 15);
-        self.color_idx = color;
-    }
-
-    fn turn(&mut self, degree: Degree) {
-        println!("turtle turn: {degree}");
-        self.direction = self.direction + degree;
-    }
-
-    fn set_heading(&mut self, degree: Degree) {
-        println!("turtle set_heading: {degree}");
-        self.direction = degree;
-    }
-
-    fn set_x_coordinate(&mut self, coordinate: Pixel) {
-        println!("turtle set_x_coordinate: {coordinate}");
-        self.x = coordinate;
-    }
-    fn set_y_coordinate(&mut self, coordinate: Pixel) {
-        println!("turtle set_y_coordinate: {coordinate}");
-        self.y = coordinate;
-    }
-
-    fn get_x_coordinate(&self) -> Pixel {
-        println!("turtle get_x_coordinate: {}", self.x);
-        self.x
-    }
-    fn get_y_coordinate(&self) -> Pixel {
-        println!("turtle get_y_coordinate: {}", self.y);
-        self.y
-    }
-    fn get_heading(&self) -> Direction {
-        println!("turtle get_heading: {}", self.direction);
-        self.direction
-    }
-    fn get_color(&self) -> Color {
-        println!("turtle get_color: {}", self.color_idx);
-        self.color_idx
-    }
-}
-
-impl Drop for Turtle {
-    fn drop(&mut self) {
-        match self.path.extension().map(|s| s.to_str()).flatten() {
-            Some("svg") => {
-                let res = self.image.save_svg(&self.path);
-                if let Err(e) = res {
-                    eprintln!("Error saving svg: {e}");
-                    // @todo error handle
-                }
-            }
-            Some("png") => {
-                let res = self.image.save_png(&self.path);
-                if let Err(e) = res {
-                    eprintln!("Error saving png: {e}");
-                    // @todo error handle
-                }
-            }
-            _ => {
-                eprintln!("File extension not supported");
-                // @todo error handle
-            }
+    pub fn line_to(&mut self, canvas: &mut Canvas, to: Point) {
+        if let Some(ink) = self.ink() {
+            canvas.line((self.x, self.y), to, ink);
         }
+        (self.x, self.y) = to;
     }
 }
@@ -0,0 +1,90 @@
+use super::{Coordinates, Pixel, Point};
+
+// where the coordinates a program uses end up on the canvas: world units are
+// scaled the same both ways so headings and circles keep their shape
+#[derive(Clone, Debug)]
+pub(super) struct World {
+    coordinates: Coordinates,
+    // SETWINDOW rectangle, fitted to the canvas and centred on it
+    window: Option<(Point, Point)>,
+    width: Pixel,
+    height: Pixel,
+}
+
+impl World {
+    pub(super) fn new(width: u32, height: u32) -> Self {
+        World {
+            coordinates: Coordinates::Canvas,
+            window: None,
+            width: width as Pixel,
+            height: height as Pixel,
+        }
+    }
+
+    pub(super) fn set_coordinates(&mut self, coordinates: Coordinates) {
+        self.coordinates = coordinates;
+    }
+
+    pub(super) fn set_window(&mut self, min: Point, max: Point) {
+        self.window = Some((min, max));
+    }
+
+    // canvas pixels per world unit
+    pub(super) fn scale(&self) -> Pixel {
+        match self.window {
+            Some((min, max)) => (self.width / (max.0 - min.0)).min(self.height / (max.1 - min.1)),
+            None => 1.0,
+        }
+    }
+
+    // world point drawn at the centre of the canvas
+    fn centre(&self) -> Point {
+        match (self.window, self.coordinates) {
+            (Some((min, max)), _) => ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0),
+            (None, Coordinates::Canvas) => (self.width / 2.0, self.height / 2.0),
+            (None, Coordinates::Centre) => (0.0, 0.0),
+        }
+    }
+
+    // canvas y grows downwards, centred coordinates point it up
+    fn flip(&self, dy: Pixel) -> Pixel {
+        match self.coordinates {
+            Coordinates::Canvas => dy,
+            Coordinates::Centre => -dy,
+        }
+    }
+
+    // canvas position of the world origin
+    fn origin(&self) -> Point {
+        let (cx, cy) = self.centre();
+        let scale = self.scale();
+        (
+            self.width / 2.0 - cx * scale,
+            self.height / 2.0 - self.flip(cy * scale),
+        )
+    }
+
+    pub(super) fn to_canvas(&self, (x, y): Point) -> Point {
+        let (ox, oy) = self.origin();
+        let scale = self.scale();
+        (ox + x * scale, oy + self.flip(y * scale))
+    }
+
+    pub(super) fn to_world(&self, (x, y): Point) -> Point {
+        let (ox, oy) = self.origin();
+        let scale = self.scale();
+        // subtracting rather than negating keeps the centre at 0, not -0
+        let dy = match self.coordinates {
+            Coordinates::Canvas => y - oy,
+            Coordinates::Centre => oy - y,
+        };
+        ((x - ox) / scale, dy / scale)
+    }
+
+    // smallest and largest world point on the canvas
+    pub(super) fn bounds(&self) -> (Point, Point) {
+        let (x0, y0) = self.to_world((0.0, 0.0));
+        let (x1, y1) = self.to_world((self.width, self.height));
+        ((x0.min(x1), y0.min(y1)), (x0.max(x1), y0.max(y1)))
+    }
+}
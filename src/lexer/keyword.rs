@@ -1,3 +1,5 @@
+// named as the commands are written
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq)]
 pub enum Keyword {
     TRUE,
@@ -5,6 +7,8 @@ pub enum Keyword {
 
     PENUP,
     PENDOWN,
+    PENERASE,
+    PENREVERSE,
     FORWARD,
     BACK,
     LEFT,
@@ -14,6 +18,27 @@ pub enum Keyword {
     SETHEADING,
     SETX,
     SETY,
+    SETXY,
+    SETPOS,
+    SETWINDOW,
+    ARC,
+    CIRCLE,
+    BEZIER,
+    LABEL,
+    SETLABELFONT,
+    SETLABELSIZE,
+    TELL,
+    ASK,
+    PUSHTURTLE,
+    POPTURTLE,
+    LSYSTEM,
+    SHOWTURTLE,
+    HIDETURTLE,
+    SETSHAPE,
+    WRAP,
+    FENCE,
+    WINDOW,
+    WAIT,
 
     MAKE,
     ADDASSIGN,
@@ -43,6 +68,8 @@ pub fn is_keyword(s: &str) -> Option<Keyword> {
 
         "PENUP" => Some(Keyword::PENUP),
         "PENDOWN" => Some(Keyword::PENDOWN),
+        "PENERASE" => Some(Keyword::PENERASE),
+        "PENREVERSE" => Some(Keyword::PENREVERSE),
         "FORWARD" => Some(Keyword::FORWARD),
         "BACK" => Some(Keyword::BACK),
         "LEFT" => Some(Keyword::LEFT),
@@ -52,6 +79,27 @@ pub fn is_keyword(s: &str) -> Option<Keyword> {
         "SETHEADING" => Some(Keyword::SETHEADING),
         "SETX" => Some(Keyword::SETX),
         "SETY" => Some(Keyword::SETY),
+        "SETXY" => Some(Keyword::SETXY),
+        "SETPOS" => Some(Keyword::SETPOS),
+        "SETWINDOW" => Some(Keyword::SETWINDOW),
+        "ARC" => Some(Keyword::ARC),
+        "CIRCLE" => Some(Keyword::CIRCLE),
+        "BEZIER" => Some(Keyword::BEZIER),
+        "LABEL" => Some(Keyword::LABEL),
+        "SETLABELFONT" => Some(Keyword::SETLABELFONT),
+        "SETLABELSIZE" => Some(Keyword::SETLABELSIZE),
+        "TELL" => Some(Keyword::TELL),
+        "ASK" => Some(Keyword::ASK),
+        "PUSHTURTLE" => Some(Keyword::PUSHTURTLE),
+        "POPTURTLE" => Some(Keyword::POPTURTLE),
+        "LSYSTEM" => Some(Keyword::LSYSTEM),
+        "SHOWTURTLE" => Some(Keyword::SHOWTURTLE),
+        "HIDETURTLE" => Some(Keyword::HIDETURTLE),
+        "SETSHAPE" => Some(Keyword::SETSHAPE),
+        "WRAP" => Some(Keyword::WRAP),
+        "FENCE" => Some(Keyword::FENCE),
+        "WINDOW" => Some(Keyword::WINDOW),
+        "WAIT" => Some(Keyword::WAIT),
 
         "MAKE" => Some(Keyword::MAKE),
         "ADDASSIGN" => Some(Keyword::ADDASSIGN),
@@ -14,15 +14,12 @@ pub struct LexerImpl {
 }
 
 fn is_comment(s: &str) -> bool {
-    match s {
-        "//" => true,
-        _ => false,
-    }
+    s == "//"
 }
 
 impl LexerImpl {
     pub fn new(source: &PathBuf) -> Self {
-        let file = fs::File::open(&source).expect("Failed to open source file");
+        let file = fs::File::open(source).expect("Failed to open source file");
 
         Self {
             buf_reader: BufReader::new(file),
@@ -39,7 +36,6 @@ impl Lexer for LexerImpl {
             Ok(0) => None,
             Ok(_) => {
                 let words: Vec<Token> = line
-                    .trim()
                     .split_whitespace()
                     .map(String::from)
                     .map(Self::distinguish)
@@ -51,29 +47,21 @@ impl Lexer for LexerImpl {
                 //     words
                 // );
                 if words.is_empty() {
-                    self.line_number = self.line_number + 1;
-                    if self.line_number == 22 {
-                        // test
-                        println!("11");
-                    }
+                    self.line_number += 1;
                     self.next_line_token()
                 } else {
-                    if is_comment(&words[0].souce.as_str()) {
-                        self.line_number = self.line_number + 1;
+                    if is_comment(words[0].souce.as_str()) {
+                        self.line_number += 1;
                         return self.next_line_token();
                     }
-                    self.line_number = self.line_number + 1;
+                    self.line_number += 1;
                     Some(words)
                 }
             }
 
             // failed to read line
             Err(e) => {
-                panic!(
-                    "Failed to read line: {}, error: {}",
-                    self.line_number,
-                    e.to_string()
-                );
+                panic!("Failed to read line: {}, error: {}", self.line_number, e);
             }
         }
     }
@@ -103,15 +91,16 @@ impl LexerImpl {
                     return Token::new(s, TokenType::Query(query));
                 }
 
+                // the word as written is kept for commands taking text
                 if let Some(str) = literal::is_literal(&s) {
-                    return Token::new(String::new(), TokenType::Float(str));
+                    return Token::new(s[1..].to_string(), TokenType::Float(str));
                 }
 
-                if let Some(_) = variable::is_variable(&s) {
+                if variable::is_variable(&s).is_some() {
                     return Token::new(s.chars().skip(1).collect(), TokenType::Variable);
                 }
 
-                if let Some(_) = procedure::is_procedure(&s) {
+                if procedure::is_procedure(&s).is_some() {
                     return Token::new(s, TokenType::Procedure);
                 }
             }
@@ -1,7 +1,7 @@
 pub mod keyword;
 mod lexer_impl;
 mod literal;
-mod procedure;
+pub mod procedure;
 mod query;
 mod token;
 pub mod token_type;
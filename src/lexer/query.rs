@@ -1,9 +1,17 @@
+// named as the commands are written
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq)]
 pub enum Query {
     XCOR,
     YCOR,
     HEADING,
     COLOR,
+    POS,
+    TOWARDS,
+    DISTANCE,
+    WHO,
+    SHOWNP,
+    PENMODE,
 }
 
 pub fn is_query(s: &str) -> Option<Query> {
@@ -12,6 +20,12 @@ pub fn is_query(s: &str) -> Option<Query> {
         "YCOR" => Some(Query::YCOR),
         "HEADING" => Some(Query::HEADING),
         "COLOR" => Some(Query::COLOR),
+        "POS" => Some(Query::POS),
+        "TOWARDS" => Some(Query::TOWARDS),
+        "DISTANCE" => Some(Query::DISTANCE),
+        "WHO" => Some(Query::WHO),
+        "SHOWNP" => Some(Query::SHOWNP),
+        "PENMODE" => Some(Query::PENMODE),
         _ => None,
     }
 }
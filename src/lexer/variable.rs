@@ -6,7 +6,7 @@ use crate::lexer::keyword;
 pub fn is_variable(s: &str) -> Option<()> {
     let re = Regex::new(r#"\"[a-zA-Z_][a-zA-Z0-9_]*[^"]*"#).unwrap();
 
-    if let Some(_) = keyword::is_keyword(s) {
+    if keyword::is_keyword(s).is_some() {
         return None;
     }
     if re.is_match(s) {
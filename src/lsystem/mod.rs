@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::error::LogoError;
+use crate::executor::{Degree, Executor, Pixel};
+
+// stop before an expansion eats all the memory
+const MAX_SYMBOLS: usize = 10_000_000;
+
+// whether every ] closes a [ opened before it, so branches only ever pop
+// the turtles they pushed
+fn balanced(symbols: &str) -> bool {
+    let mut depth = 0usize;
+    for symbol in symbols.chars() {
+        match symbol {
+            '[' => depth += 1,
+            ']' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+// symbols follow the usual turtle interpretation:
+// F G draw forward, f move forward, + - turn left/right, | turn around,
+// [ ] push/pop the turtle, anything else only takes part in rewriting
+pub struct LSystem {
+    axiom: String,
+    rules: HashMap<char, String>,
+}
+
+impl LSystem {
+    // rules are space separated, each like F=F+F-F
+    pub fn new(axiom: &str, rules: &str) -> Result<Self, LogoError> {
+        if !balanced(axiom) {
+            return Err(LogoError::InvalidLSystem(format!("brackets in {axiom}")));
+        }
+        let mut table = HashMap::new();
+        for rule in rules.split_whitespace() {
+            let mut chars = rule.chars();
+            match (chars.next(), chars.next()) {
+                (Some(symbol), Some('=')) => {
+                    let replacement: String = chars.collect();
+                    if !balanced(&replacement) {
+                        return Err(LogoError::InvalidLSystem(format!("brackets in {rule}")));
+                    }
+                    table.insert(symbol, replacement);
+                }
+                _ => return Err(LogoError::InvalidLSystem(format!("rule {rule}"))),
+            }
+        }
+
+        Ok(LSystem {
+            axiom: axiom.to_string(),
+            rules: table,
+        })
+    }
+
+    pub fn expand(&self, iterations: usize) -> Result<String, LogoError> {
+        let mut current = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = String::with_capacity(current.len() * 2);
+            for symbol in current.chars() {
+                match self.rules.get(&symbol) {
+                    Some(replacement) => next.push_str(replacement),
+                    None => next.push(symbol),
+                }
+                if next.len() > MAX_SYMBOLS {
+                    return Err(LogoError::InvalidLSystem(format!(
+                        "more than {MAX_SYMBOLS} symbols after expansion"
+                    )));
+                }
+            }
+            current = next;
+        }
+        Ok(current)
+    }
+
+    pub fn draw(
+        &self,
+        executor: &mut Box<dyn Executor>,
+        iterations: f32,
+        step: Pixel,
+        angle: Degree,
+    ) -> Result<(), LogoError> {
+        if iterations < 0.0 || iterations.fract() != 0.0 {
+            return Err(LogoError::InvalidLSystem(format!(
+                "iterations {iterations}"
+            )));
+        }
+
+        for symbol in self.expand(iterations as usize)?.chars() {
+            match symbol {
+                'F' | 'G' => executor.foreward(step)?,
+                'f' => executor.jump(step)?,
+                '+' => executor.turn(-angle),
+                '-' => executor.turn(angle),
+                '|' => executor.turn(180.0),
+                '[' => executor.push_turtle(),
+                ']' => executor.pop_turtle()?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
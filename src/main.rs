@@ -2,30 +2,303 @@ mod ast;
 mod error;
 mod executor;
 mod lexer;
+mod lsystem;
 mod parser;
+mod replay;
 
 use ast::{ASTNode, FunName};
 use clap::Parser as clapParser;
+use std::io::Write;
 
 use crate::{error::fatal_error, error::LogoError, parser::parse_as_number};
 
 #[derive(clapParser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // without a subcommand the arguments are the ones of `run`
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Run a logo program and draw it
+    Run(RunArgs),
+    /// Draw a trace written by `run --emit-trace` without running the program again
+    Replay(ReplayArgs),
+}
+
+#[derive(clap::Args)]
+struct RunArgs {
+    // optional only so the same arguments can stand without a subcommand
     /// Path to a file
-    file_path: std::path::PathBuf,
+    #[arg(required = true)]
+    file_path: Option<std::path::PathBuf>,
 
-    /// Path to an svg or png image
-    image_path: std::path::PathBuf,
+    /// Image to write, by extension: svg, png, gcode or nc, hpgl or plt, pdf, eps, html or htm
+    #[arg(required = true)]
+    image_path: Option<std::path::PathBuf>,
 
     /// Height
-    height: u32,
-    width: u32,
+    #[arg(required = true)]
+    height: Option<u32>,
+    #[arg(required = true)]
+    width: Option<u32>,
+
+    /// How SETX, SETY, SETXY, XCOR and YCOR read positions: canvas pixels
+    /// from the top left, or centred on the canvas with y pointing up
+    #[arg(long, value_enum, default_value_t = executor::Coordinates::Canvas)]
+    coordinates: executor::Coordinates,
+
+    /// Write every drawing call to a file as json lines, for `replay`
+    #[arg(long)]
+    emit_trace: Option<std::path::PathBuf>,
+
+    /// Save what was drawn when the program stops on an error
+    #[arg(long)]
+    keep_partial: bool,
+
+    /// Mark the turtle position and line where the program stopped
+    #[arg(long, requires = "keep_partial")]
+    mark_stop: bool,
+
+    #[command(flatten)]
+    image: ImageArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(clap::Args)]
+struct ReplayArgs {
+    /// Trace written by `run --emit-trace`
+    trace_path: std::path::PathBuf,
+
+    /// Image to write, by extension: svg, png, gcode or nc, hpgl or plt, pdf, eps, html or htm
+    image_path: std::path::PathBuf,
+
+    /// Height, the one the trace was recorded with by default
+    #[arg(requires = "width")]
+    height: Option<u32>,
+    width: Option<u32>,
+
+    #[command(flatten)]
+    image: ImageArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+// how the image file is written
+#[derive(clap::Args)]
+struct ImageArgs {
+    /// Decimals kept for svg coordinates
+    #[arg(long, default_value_t = executor::ImageOptions::default().precision)]
+    svg_precision: usize,
+
+    /// Wrap each procedure called by the main program in an svg group
+    #[arg(long)]
+    svg_groups: bool,
+
+    /// Put each pen colour in its own Inkscape layer of the svg
+    #[arg(long)]
+    layers: bool,
+
+    /// Write one file per pen colour, its number added to the file name
+    #[arg(long)]
+    split_colors: bool,
+
+    /// Image size relative to the canvas, 2 doubles width and height
+    #[arg(long, default_value_t = 1.0, value_parser = positive)]
+    scale: f32,
+
+    /// Samples per pixel in each direction when rendering a png
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=16))]
+    supersample: u32,
+
+    /// Draw png edges without anti-aliasing
+    #[arg(long)]
+    no_antialias: bool,
+
+    /// Leave out the black background, not with a pen that erases
+    #[arg(long)]
+    transparent: bool,
+
+    /// Resolution stored in png files
+    #[arg(long, default_value_t = 96.0, value_parser = positive)]
+    dpi: f32,
+
+    /// Scale and centre the drawing to fill the image
+    #[arg(long, conflicts_with = "auto_size")]
+    fit: bool,
+
+    /// Size the image to the drawing instead of HEIGHT and WIDTH, which still
+    /// set where the turtle starts and where the edges are
+    #[arg(long)]
+    auto_size: bool,
+
+    /// Pixels kept clear around a fitted or auto-sized drawing
+    #[arg(long, default_value_t = executor::ImageOptions::default().margin, value_parser = non_negative)]
+    margin: f32,
+
+    /// Drop repeated segments, simplify within TOLERANCE pixels and reorder paths
+    /// to cut pen-up travel; curves are written as lines
+    #[arg(long, value_name = "TOLERANCE", num_args = 0..=1, require_equals = true,
+          default_missing_value = "0.1", value_parser = non_negative)]
+    optimize: Option<f32>,
+
+    /// Draw the image in the terminal with braille characters as well
+    #[arg(long)]
+    preview: bool,
+
+    /// Only show the preview, without writing the image file
+    #[arg(long, requires = "preview")]
+    no_save: bool,
+
+    /// Play the drawing back in execution order: an animated svg, or a
+    /// numbered png per frame
+    #[arg(long, conflicts_with_all = ["svg_groups", "layers", "split_colors"])]
+    animate: bool,
+
+    /// Frames per second of the animation
+    #[arg(long, default_value_t = 30.0, value_parser = positive, requires = "animate")]
+    fps: f32,
+
+    /// Lines, curves and labels added by each frame of the animation
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), requires = "animate")]
+    segments_per_frame: u32,
+
+    #[command(flatten)]
+    plotter: PlotterArgs,
+}
+
+// g-code and hpgl output
+#[derive(clap::Args)]
+#[command(next_help_heading = "Plotter")]
+struct PlotterArgs {
+    /// Paper width in millimetres, the drawing is fitted and centred
+    #[arg(long, default_value_t = executor::PlotterOptions::default().paper_width, value_parser = positive)]
+    paper_width: f32,
+
+    /// Paper height in millimetres
+    #[arg(long, default_value_t = executor::PlotterOptions::default().paper_height, value_parser = positive)]
+    paper_height: f32,
+
+    /// Drawing speed in millimetres per minute
+    #[arg(long, default_value_t = executor::PlotterOptions::default().feed_rate, value_parser = positive)]
+    feed_rate: f32,
+
+    /// G-code lifting the pen, e.g. "M3 S30" for a servo
+    #[arg(long, default_value_t = executor::PlotterOptions::default().pen_up)]
+    pen_up: String,
+
+    /// G-code lowering the pen
+    #[arg(long, default_value_t = executor::PlotterOptions::default().pen_down)]
+    pen_down: String,
+
+    /// G-code pausing to change the pen when the colour changes
+    #[arg(long, default_value_t = executor::PlotterOptions::default().pen_change)]
+    pen_change: String,
+}
+
+fn positive(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(v) if v > 0.0 && v.is_finite() => Ok(v),
+        _ => Err(format!("{value} is not a positive number")),
+    }
+}
+
+fn non_negative(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(v) if v >= 0.0 && v.is_finite() => Ok(v),
+        _ => Err(format!("{value} is not zero or a positive number")),
+    }
+}
+
+impl ImageArgs {
+    fn options(&self) -> executor::ImageOptions {
+        executor::ImageOptions {
+            precision: self.svg_precision,
+            groups: self.svg_groups,
+            layers: self.layers,
+            split_colors: self.split_colors,
+            scale: self.scale,
+            supersample: self.supersample,
+            antialias: !self.no_antialias,
+            transparent: self.transparent,
+            dpi: self.dpi,
+            placement: if self.fit {
+                executor::Placement::Fit
+            } else if self.auto_size {
+                executor::Placement::AutoSize
+            } else {
+                executor::Placement::AsDrawn
+            },
+            margin: self.margin,
+            optimize: self.optimize,
+            preview: self.preview,
+            save: !self.no_save,
+            animation: self.animate.then_some(executor::AnimationOptions {
+                fps: self.fps,
+                segments_per_frame: self.segments_per_frame as usize,
+            }),
+            plotter: executor::PlotterOptions {
+                paper_width: self.plotter.paper_width,
+                paper_height: self.plotter.paper_height,
+                feed_rate: self.plotter.feed_rate,
+                pen_up: self.plotter.pen_up.clone(),
+                pen_down: self.plotter.pen_down.clone(),
+                pen_change: self.plotter.pen_change.clone(),
+            },
+        }
+    }
+}
+
+// diagnostics shared by every way of drawing
+#[derive(clap::Args)]
+#[command(next_help_heading = "Output")]
+struct OutputArgs {
+    /// Report every executed command with the turtle state it leads to
+    #[arg(long)]
+    trace: bool,
+
+    /// How trace events are written
+    #[arg(long, value_enum, default_value_t = executor::TraceFormat::Text, requires = "trace")]
+    trace_format: executor::TraceFormat,
+
+    /// Write the trace to a file instead of stderr
+    #[arg(long, requires = "trace")]
+    trace_output: Option<std::path::PathBuf>,
+
+    /// Write the drawing operations to a file, one per line
+    #[arg(long)]
+    record: Option<std::path::PathBuf>,
+}
+
+fn turtle_id(val: f32) -> executor::TurtleId {
+    if val < 0.0 || val.fract() != 0.0 {
+        fatal_error(LogoError::InvalidTurtle(val.to_string()));
+    }
+    val as executor::TurtleId
+}
+
+fn pen_color(val: f32) -> executor::Color {
+    if !(0.0..=15.0).contains(&val) || val.fract() != 0.0 {
+        fatal_error(LogoError::InvalidColor(val.to_string()));
+    }
+    val as executor::Color
 }
 
 struct Manager {
     variables: std::collections::HashMap<String, String>,
 
     pub func_vars: std::collections::HashMap<String, Vec<String>>,
+
+    // procedures being run, 0 while in the main program
+    call_depth: usize,
 }
 
 impl Manager {
@@ -33,6 +306,7 @@ impl Manager {
         Self {
             variables: std::collections::HashMap::new(),
             func_vars: std::collections::HashMap::new(),
+            call_depth: 0,
         }
     }
 
@@ -48,23 +322,31 @@ impl Manager {
                     self.dfs(node, executor, runtime);
                 }
             }
+            ASTNode::Line(line, node) => {
+                executor.set_source_line(*line);
+                self.dfs(node, executor, runtime);
+            }
             ASTNode::FunctionCall(fun_name, args) => match fun_name {
                 FunName::PenUp => executor.pen_up(),
                 FunName::PenDown => executor.pen_down(),
+                FunName::PenErase => executor.pen_erase(),
+                FunName::PenReverse => executor.pen_reverse(),
                 FunName::Foreward => {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("FOREWARD".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.foreward(v);
+                            if let Err(e) = executor.foreward(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -76,16 +358,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("BACK".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.back(v);
+                            if let Err(e) = executor.back(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -97,16 +381,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack: Vec<f32> = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("LEFT".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.left(v);
+                            if let Err(e) = executor.left(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -118,16 +404,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("RIGHT".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.right(v);
+                            if let Err(e) = executor.right(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -139,16 +427,16 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("SETCOLOR".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.set_color(v as u32);
+                            executor.set_color(pen_color(v));
                         } else {
                             panic!("not u32");
                         }
@@ -160,18 +448,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("TURN".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.turn(v as i32);
+                            executor.turn(v);
                         } else {
-                            panic!("not i32");
+                            panic!("not f32");
                         }
                     } else {
                         panic!("few argument");
@@ -181,10 +469,10 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression(
@@ -193,9 +481,9 @@ impl Manager {
                                 ));
                             }
                             assert!(args.len() == 1);
-                            executor.set_heading(v as i32);
+                            executor.set_heading(v);
                         } else {
-                            panic!("not i32");
+                            panic!("not f32");
                         }
                     } else {
                         panic!("few argument");
@@ -205,16 +493,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("SETX".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.set_x_coordinate(v);
+                            if let Err(e) = executor.set_x_coordinate(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -226,16 +516,18 @@ impl Manager {
                     if let Some(args) = args {
                         let mut stack: Vec<f32> = Vec::new();
                         if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
-                            if stack.len() != 0 {
+                            if !stack.is_empty() {
                                 let mut str = String::new();
-                                for i in 0..args.len() {
-                                    str.push_str(&args[i]);
+                                for arg in args.iter() {
+                                    str.push_str(arg);
                                     str.push(' ');
                                 }
                                 fatal_error(LogoError::NotAexpression("SETY".to_string(), str));
                             }
                             assert!(args.len() == 1);
-                            executor.set_y_coordinate(v);
+                            if let Err(e) = executor.set_y_coordinate(v) {
+                                fatal_error(e);
+                            }
                         } else {
                             panic!("not f32");
                         }
@@ -243,12 +535,118 @@ impl Manager {
                         panic!("few argument");
                     }
                 }
+                FunName::SetPosition | FunName::SetPositionList => {
+                    // errors name the command as the program wrote it
+                    let command = match fun_name {
+                        FunName::SetPositionList => "SETPOS",
+                        _ => "SETXY",
+                    };
+                    let values = self.evaluate_arguments(command, args, 2, executor);
+                    if let Err(e) = executor.set_position(values[0], values[1]) {
+                        fatal_error(e);
+                    }
+                }
+                FunName::SetWindow => {
+                    let values = self.evaluate_arguments("SETWINDOW", args, 4, executor);
+                    let result =
+                        executor.set_window((values[0], values[1]), (values[2], values[3]));
+                    if let Err(e) = result {
+                        fatal_error(e);
+                    }
+                }
+                FunName::Arc => {
+                    let values = self.evaluate_arguments("ARC", args, 2, executor);
+                    executor.arc(values[0], values[1]);
+                }
+                FunName::Circle => {
+                    let values = self.evaluate_arguments("CIRCLE", args, 1, executor);
+                    executor.circle(values[0]);
+                }
+                FunName::Bezier => {
+                    let values = self.evaluate_arguments("BEZIER", args, 6, executor);
+                    let result = executor.bezier(
+                        (values[0], values[1]),
+                        (values[2], values[3]),
+                        (values[4], values[5]),
+                    );
+                    if let Err(e) = result {
+                        fatal_error(e);
+                    }
+                }
+                FunName::Label => {
+                    if let Some(args) = args {
+                        executor.label(&self.words(&args[0]));
+                    } else {
+                        panic!("few argument");
+                    }
+                }
+                FunName::SetLabelFont => {
+                    if let Some(args) = args {
+                        executor.set_label_font(&self.words(&args[0]));
+                    } else {
+                        panic!("few argument");
+                    }
+                }
+                FunName::SetLabelSize => {
+                    let values = self.evaluate_arguments("SETLABELSIZE", args, 1, executor);
+                    executor.set_label_size(values[0]);
+                }
+                FunName::LSystem => {
+                    let Some(args) = args else {
+                        panic!("few argument");
+                    };
+                    let values = self.evaluate_arguments(
+                        "LSYSTEM",
+                        &Some(vec![args[2].clone()]),
+                        3,
+                        executor,
+                    );
+                    let result = lsystem::LSystem::new(&args[0], &args[1])
+                        .and_then(|system| system.draw(executor, values[0], values[1], values[2]));
+                    if let Err(e) = result {
+                        fatal_error(e);
+                    }
+                }
+                FunName::Wrap => executor.set_edge_mode(executor::EdgeMode::Wrap),
+                FunName::Fence => executor.set_edge_mode(executor::EdgeMode::Fence),
+                FunName::Window => executor.set_edge_mode(executor::EdgeMode::Window),
+                FunName::Wait => {
+                    let values = self.evaluate_arguments("WAIT", args, 1, executor);
+                    executor.wait(values[0]);
+                }
+                FunName::ShowTurtle => executor.show_turtle(),
+                FunName::HideTurtle => executor.hide_turtle(),
+                FunName::SetShape => {
+                    if let Some(args) = args {
+                        let words = self.words(&args[0]);
+                        if let Some(shape) = executor::TurtleShape::from_words(&words) {
+                            executor.set_shape(shape);
+                        } else {
+                            fatal_error(LogoError::InvalidShape(words));
+                        }
+                    } else {
+                        panic!("few argument");
+                    }
+                }
+                FunName::PushTurtle => executor.push_turtle(),
+                FunName::PopTurtle => {
+                    if let Err(e) = executor.pop_turtle() {
+                        fatal_error(e);
+                    }
+                }
+                FunName::Tell => {
+                    let values = self.evaluate_arguments("TELL", args, 1, executor);
+                    executor.tell(turtle_id(values[0]));
+                }
             },
             ASTNode::Define(name, expressions) => {
                 let mut stack: Vec<f32> = Vec::new();
 
                 if let Some(v) = self.evaluate_prefix(&mut stack, expressions, executor) {
-                    self.variables.insert(name.clone(), v.to_string());
+                    // a list such as POS leaves its remaining items on the stack
+                    stack.push(v);
+                    let items: Vec<String> = stack.iter().rev().map(|v| v.to_string()).collect();
+                    self.variables.insert(name.clone(), items.join(" "));
                 } else {
                     panic!("failed to obtain expression val");
                 }
@@ -258,7 +656,17 @@ impl Manager {
 
                 if let Some(v) = self.evaluate_prefix(&mut stack, expressions, executor) {
                     if let Some(old) = self.variables.get(name) {
-                        let old = parse_as_number::<f32>(old).expect("error parse");
+                        // lists such as POS cannot be added to
+                        let Some(old) = parse_as_number::<f32>(old) else {
+                            fatal_error(LogoError::NotANumber(name.clone(), old.clone()));
+                            unreachable!();
+                        };
+                        if !stack.is_empty() {
+                            fatal_error(LogoError::NotAexpression(
+                                "ADDASSIGN".to_string(),
+                                expressions.clone(),
+                            ));
+                        }
                         self.variables.insert(name.clone(), (v + old).to_string());
                     } else {
                         panic!("not defined variable for {}", name);
@@ -272,7 +680,7 @@ impl Manager {
                     // 在这里处理 expression 是 Expersion 的情况
                     let mut stack: Vec<f32> = Vec::new();
 
-                    if let Some(val) = self.evaluate_prefix(&mut stack, &str, executor) {
+                    if let Some(val) = self.evaluate_prefix(&mut stack, str, executor) {
                         if val != 0.0 {
                             // 执行 IF 语句块
                             for statement in block {
@@ -289,10 +697,10 @@ impl Manager {
                     loop {
                         let mut stack: Vec<f32> = Vec::new();
 
-                        if let Some(val) = self.evaluate_prefix(&mut stack, &str, executor) {
+                        if let Some(val) = self.evaluate_prefix(&mut stack, str, executor) {
                             if val != 0.0 {
                                 // 执行 IF 语句块
-                                for statement in &*block {
+                                for statement in block {
                                     self.dfs(statement, executor, runtime);
                                 }
                             } else {
@@ -304,96 +712,212 @@ impl Manager {
                     panic!("if doesn't meet expression");
                 }
             }
-            ASTNode::CustomFunction(func_name, expression) => {
-                let argument_size = self.func_vars[func_name].len();
+            ASTNode::Ask(expression, block) => {
+                if let ASTNode::Expersion(str) = &**expression {
+                    let mut stack: Vec<f32> = Vec::new();
 
-                if argument_size == 0 {
-                    if let None = expression {
-                        let statements = &runtime[func_name];
-                        for node in statements {
-                            self.dfs(&node, executor, runtime);
+                    if let Some(val) = self.evaluate_prefix(&mut stack, str, executor) {
+                        // run the block as the asked turtle, then hand control back
+                        let previous = executor.who();
+                        executor.tell(turtle_id(val));
+                        for statement in block {
+                            self.dfs(statement, executor, runtime);
                         }
-                    } else {
-                        panic!("no arguments");
+                        executor.tell(previous);
                     }
                 } else {
-                    if let Some(expression) = expression {
-                        // calcatue parameter value;
-                        let mut stack: Vec<f32> = Vec::new();
-                        if let Some(val) = self.evaluate_prefix(&mut stack, &expression, executor) {
-                            stack.push(val);
-                        } else {
-                            panic!("no enough para");
-                        }
+                    panic!("ask doesn't meet expression");
+                }
+            }
+            ASTNode::CustomFunction(func_name, expression) => {
+                // only calls made by the main program get a group of their own
+                let top_level = self.call_depth == 0;
+                if top_level {
+                    executor.begin_group(func_name);
+                }
+                self.call_depth += 1;
+                self.call_function(func_name, expression, executor, runtime);
+                self.call_depth -= 1;
+                if top_level {
+                    executor.end_group();
+                }
+            }
+            _ => {
+                panic!("Attempted to push into a non-Sequence variant of ASTNode");
+            }
+        }
+    }
 
-                        if stack.len() != argument_size {
-                            panic!("no enough parameter value");
-                        }
+    fn call_function(
+        &mut self,
+        func_name: &str,
+        expression: &Option<String>,
+        executor: &mut Box<dyn executor::Executor>,
+        runtime: &std::collections::HashMap<String, Vec<ASTNode>>,
+    ) {
+        let argument_size = self.func_vars[func_name].len();
 
-                        //
-                        let mut odd: std::collections::HashMap<String, String> =
-                            std::collections::HashMap::new(); // arguments
+        if argument_size == 0 {
+            if expression.is_none() {
+                let statements = &runtime[func_name];
+                for node in statements {
+                    self.dfs(node, executor, runtime);
+                }
+            } else {
+                panic!("no arguments");
+            }
+        } else {
+            if let Some(expression) = expression {
+                // calcatue parameter value;
+                let mut stack: Vec<f32> = Vec::new();
+                if let Some(val) = self.evaluate_prefix(&mut stack, expression, executor) {
+                    stack.push(val);
+                } else {
+                    panic!("no enough para");
+                }
 
-                        for var_name in self.func_vars[func_name].iter() {
-                            if self.variables.contains_key(var_name) {
-                                odd.insert(
-                                    var_name.clone(),
-                                    self.variables.get(var_name).expect("unreach").clone(),
-                                );
-                            }
-                            self.variables.insert(
-                                var_name.clone(),
-                                stack.pop().expect("unreach").to_string(),
-                            );
-                        }
-                        let statements = &runtime[func_name];
-                        for node in statements {
-                            self.dfs(&node, executor, runtime);
-                        }
+                if stack.len() != argument_size {
+                    panic!("no enough parameter value");
+                }
 
-                        for var_name in self.func_vars[func_name].iter().rev() {
-                            if odd.contains_key(var_name) {
-                                self.variables
-                                    .insert(var_name.clone(), odd[var_name].clone());
-                            } else {
-                                self.variables.remove(var_name);
-                            }
-                        }
+                //
+                let mut odd: std::collections::HashMap<String, String> =
+                    std::collections::HashMap::new(); // arguments
+
+                for var_name in self.func_vars[func_name].iter() {
+                    if self.variables.contains_key(var_name) {
+                        odd.insert(
+                            var_name.clone(),
+                            self.variables.get(var_name).expect("unreach").clone(),
+                        );
+                    }
+                    self.variables
+                        .insert(var_name.clone(), stack.pop().expect("unreach").to_string());
+                }
+                let statements = &runtime[func_name];
+                for node in statements {
+                    self.dfs(node, executor, runtime);
+                }
+
+                for var_name in self.func_vars[func_name].iter().rev() {
+                    if odd.contains_key(var_name) {
+                        self.variables
+                            .insert(var_name.clone(), odd[var_name].clone());
+                    } else {
+                        self.variables.remove(var_name);
                     }
                 }
-            }
-            _ => {
-                panic!("Attempted to push into a non-Sequence variant of ASTNode");
             }
         }
     }
 
-    pub fn get_variable_val(&self, name: &str) -> Option<f32> {
+    // a variable holds either a number or a list of numbers
+    pub fn get_variable_val(&self, name: &str) -> Option<Vec<f32>> {
         for (k, v) in &self.variables {
             if k == &name[1..] {
-                if let Some(v) = parse_as_number::<f32>(v) {
-                    return Some(v);
-                } else {
-                    panic!("cannot parse");
+                let mut items = Vec::new();
+                for item in v.split_whitespace() {
+                    if let Some(v) = parse_as_number::<f32>(item) {
+                        items.push(v);
+                    } else {
+                        panic!("cannot parse");
+                    }
                 }
+                return Some(items);
             }
         }
         None
     }
 
+    // words of a command taking text, each :variable replaced by what it holds
+    fn words(&self, text: &str) -> String {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|word| {
+                if lexer::procedure::is_procedure(word).is_none() {
+                    return word.to_string();
+                }
+                match self.variables.get(&word[1..]) {
+                    Some(value) => value.clone(),
+                    None => {
+                        fatal_error(LogoError::UnDefinedVariable(word.to_string()));
+                        unreachable!();
+                    }
+                }
+            })
+            .collect();
+        words.join(" ")
+    }
+
+    // evaluates a command taking `count` expressions, first argument first
+    fn evaluate_arguments(
+        &self,
+        command: &str,
+        args: &Option<Vec<String>>,
+        count: usize,
+        executor: &mut Box<dyn executor::Executor>,
+    ) -> Vec<f32> {
+        let Some(args) = args else {
+            fatal_error(LogoError::NoEnoughArguments(command.to_string()));
+            unreachable!();
+        };
+        let mut stack: Vec<f32> = Vec::new();
+        if let Some(v) = self.evaluate_prefix(&mut stack, &args[0], executor) {
+            stack.push(v);
+        }
+        if stack.len() < count {
+            fatal_error(LogoError::NoEnoughArguments(command.to_string()));
+        }
+        if stack.len() > count {
+            fatal_error(LogoError::NotAexpression(
+                command.to_string(),
+                args.join(" "),
+            ));
+        }
+        stack.into_iter().rev().collect()
+    }
+
     pub fn evaluate_prefix(
         &self,
         stack: &mut Vec<f32>,
         expressions: &str,
         executor: &mut Box<dyn executor::Executor>,
     ) -> Option<f32> {
-        for expression in expressions.split_whitespace().into_iter().rev() {
+        for expression in expressions.split_whitespace().rev() {
             match expression {
                 // query
                 "XCOR" => stack.push(executor.get_x_coordinate()),
                 "YCOR" => stack.push(executor.get_y_coordinate()),
-                "HEADING" => stack.push(executor.get_heading() as f32),
+                "HEADING" => stack.push(executor.get_heading()),
                 "COLOR" => stack.push(executor.get_color() as f32),
+                "WHO" => stack.push(executor.who() as f32),
+                // 0 up, 1 paint, 2 erase, 3 reverse
+                "PENMODE" => stack.push(match executor.get_pen_mode() {
+                    executor::PenMode::Up => 0.0,
+                    executor::PenMode::Paint => 1.0,
+                    executor::PenMode::Erase => 2.0,
+                    executor::PenMode::Reverse => 3.0,
+                }),
+                "SHOWNP" => stack.push(if executor.is_shown() { 1.0 } else { 0.0 }),
+                // a list, x ends up on top so it is consumed first
+                "POS" => {
+                    stack.push(executor.get_y_coordinate());
+                    stack.push(executor.get_x_coordinate());
+                }
+                "TOWARDS" => {
+                    if let (Some(x), Some(y)) = (stack.pop(), stack.pop()) {
+                        stack.push(executor.towards(x, y));
+                    } else {
+                        panic!("no enough opcode");
+                    }
+                }
+                "DISTANCE" => {
+                    if let (Some(x), Some(y)) = (stack.pop(), stack.pop()) {
+                        stack.push(executor.distance(x, y));
+                    } else {
+                        panic!("no enough opcode");
+                    }
+                }
                 "EQ" => {
                     if let (Some(op1), Some(op2)) = (stack.pop(), stack.pop()) {
                         stack.push(if op1 == op2 { 1.0 } else { 0.0 })
@@ -476,7 +1000,8 @@ impl Manager {
                     } else {
                         // println!("variable name: {}", expression);
                         if let Some(v) = self.get_variable_val(expression) {
-                            stack.push(v);
+                            // the first item of a list ends up on top
+                            stack.extend(v.into_iter().rev());
                         } else {
                             // not defined variable;
                             fatal_error(LogoError::UnDefinedVariable(expression.to_string()));
@@ -491,22 +1016,91 @@ impl Manager {
     }
 }
 
-fn main() -> Result<(), ()> {
-    let args: Args = Args::parse();
+// checked before running anything, so a typo does not cost a whole run
+fn image_format(path: &std::path::Path, image: &ImageArgs) -> Result<executor::ImageFormat, ()> {
+    let format = executor::ImageFormat::from_path(path).ok_or_else(|| {
+        eprintln!("image file extension not supported");
+    })?;
+    if image.animate
+        && !matches!(
+            format,
+            executor::ImageFormat::Svg | executor::ImageFormat::Png
+        )
+    {
+        eprintln!("only svg and png images can be animated");
+        return Err(());
+    }
+    Ok(format)
+}
+
+// each line is flushed on its own so a fatal error keeps what was written so far
+fn create_output(path: &std::path::Path, what: &str) -> Result<Box<dyn std::io::Write>, ()> {
+    match std::fs::File::create(path) {
+        Ok(file) => Ok(Box::new(std::io::LineWriter::new(file))),
+        Err(e) => {
+            eprintln!("Error creating {what} file {}: {e}", path.display());
+            Err(())
+        }
+    }
+}
+
+// wraps the executor into the requested recorders and tracers
+fn decorate(
+    mut executor: Box<dyn executor::Executor>,
+    output: OutputArgs,
+) -> Result<Box<dyn executor::Executor>, ()> {
+    if let Some(path) = output.record {
+        let out = create_output(&path, "record")?;
+        executor = executor::ExecutorFactory::create_recorder(executor, out);
+    }
+    if output.trace {
+        let out = match output.trace_output {
+            Some(path) => create_output(&path, "trace")?,
+            None => Box::new(std::io::stderr()),
+        };
+        executor = executor::ExecutorFactory::create_tracer(executor, output.trace_format, out);
+    }
+    Ok(executor)
+}
 
-    // Access the parsed arguments
-    let file_path = args.file_path;
-    let image_path = args.image_path;
-    let height = args.height;
-    let width = args.width;
+fn run(args: RunArgs) -> Result<(), ()> {
+    // clap makes sure they are given
+    let file_path = args.file_path.expect("file path");
+    let image_path = args.image_path.expect("image path");
+    let height = args.height.expect("height");
+    let width = args.width.expect("width");
 
-    match file_path.extension().map(|s| s.to_str()).flatten() {
+    match file_path.extension().and_then(|s| s.to_str()) {
         Some("lg") => {}
         _ => {
             eprintln!("source file extension not supported");
             return Err(());
         }
     }
+    let format = image_format(&image_path, &args.image)?;
+
+    let mut executor = executor::ExecutorFactory::create_screen(
+        width,
+        height,
+        image_path,
+        format,
+        args.image.options(),
+    );
+    executor.set_coordinates(args.coordinates);
+    if let Some(path) = args.emit_trace {
+        let mut out = create_output(&path, "trace")?;
+        if writeln!(out, "{}", replay::header(width, height, args.coordinates)).is_err() {
+            eprintln!("Error writing trace file {}", path.display());
+            return Err(());
+        }
+        executor =
+            executor::ExecutorFactory::create_tracer(executor, executor::TraceFormat::Json, out);
+    }
+    let mut executor = decorate(executor, args.output)?;
+    // the lexer reads the file on its own, this copy is only shown
+    if let Ok(source) = std::fs::read_to_string(&file_path) {
+        executor.set_source(&source);
+    }
 
     let mut manger: Manager = Manager::new();
 
@@ -517,8 +1111,65 @@ fn main() -> Result<(), ()> {
     parser.run();
 
     let root: ASTNode = parser.get_root();
-    let mut executor = executor::ExecutorFactory::create_turtle(width, height, image_path);
 
-    manger.dfs(&root, &mut executor, &function_table);
+    let finished = if args.keep_partial {
+        error::keep_partial(|| manger.dfs(&root, &mut executor, &function_table))
+    } else {
+        manger.dfs(&root, &mut executor, &function_table);
+        true
+    };
+    if !finished && args.mark_stop {
+        executor.mark_stopped();
+    }
+    if let Err(e) = executor.finish() {
+        fatal_error(e);
+    }
+    if !finished {
+        // the error is reported already, only the exit code is left
+        std::process::exit(1);
+    }
     Ok(())
 }
+
+fn replay(args: ReplayArgs) -> Result<(), ()> {
+    let format = image_format(&args.image_path, &args.image)?;
+    let trace = match replay::Trace::load(&args.trace_path) {
+        Ok(trace) => trace,
+        Err(e) => {
+            fatal_error(e);
+            unreachable!()
+        }
+    };
+
+    let (width, height) = match (args.width, args.height, trace.size()) {
+        (Some(width), Some(height), _) => (width, height),
+        (None, None, Some(size)) => size,
+        _ => {
+            eprintln!("the trace does not say its size, give height and width");
+            return Err(());
+        }
+    };
+
+    let executor = executor::ExecutorFactory::create_screen(
+        width,
+        height,
+        args.image_path,
+        format,
+        args.image.options(),
+    );
+    let mut executor = decorate(executor, args.output)?;
+    if let Err(e) = trace.replay(&mut executor).and_then(|_| executor.finish()) {
+        fatal_error(e);
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), ()> {
+    let args: Args = Args::parse();
+
+    match args.command {
+        Some(Command::Run(args)) => run(args),
+        Some(Command::Replay(args)) => replay(args),
+        None => run(args.run),
+    }
+}
@@ -10,22 +10,18 @@ use crate::parser::expression::is_expression;
 use crate::{error, Manager};
 
 pub fn parse_as_number<T: FromStr>(s: &str) -> Option<T> {
-    if let Ok(number) = s.parse::<T>() {
-        Some(number)
-    } else {
-        None
-    }
+    s.parse::<T>().ok()
 }
 
-fn sequence_handing(name: FunName, tokens: &Vec<Token>) -> ASTNode {
+fn sequence_handing(name: FunName, tokens: &[Token]) -> ASTNode {
     if tokens.len() < 2 {
         fatal_error(error::LogoError::NoEnoughArguments(tokens[0].souce.clone()));
     }
     assert!(tokens.len() >= 2);
     let mut joined_string = String::new();
 
-    for i in 1..tokens.len() {
-        if let Some(str) = is_expression(&tokens[i]) {
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        if let Some(str) = is_expression(token) {
             if i != 1 {
                 joined_string.push(' ');
             }
@@ -41,7 +37,96 @@ fn sequence_handing(name: FunName, tokens: &Vec<Token>) -> ASTNode {
     ASTNode::FunctionCall(name, Some(vec![joined_string]))
 }
 
-fn plus_and_handling(tokens: &Vec<Token>) -> ASTNode {
+// SETPOS [ x y ] takes a list, the brackets are only syntax
+fn strip_list(mut tokens: Vec<Token>) -> Vec<Token> {
+    if tokens.len() >= 3
+        && tokens[1].token_type == TokenType::LSBracket
+        && tokens[tokens.len() - 1].token_type == TokenType::RSBracket
+    {
+        tokens.pop();
+        tokens.remove(1);
+    }
+    tokens
+}
+
+// a quoted word or a [ list of words ], kept as plain text
+fn words_handling(name: FunName, tokens: &[Token]) -> ASTNode {
+    if tokens.len() < 2 {
+        fatal_error(error::LogoError::NoEnoughArguments(tokens[0].souce.clone()));
+    }
+    // numbers as written, :variables are looked up when the command runs
+    let word = |token: &Token| token.souce.clone();
+
+    let words: Vec<String> = if tokens[1].token_type == TokenType::LSBracket {
+        if tokens[tokens.len() - 1].token_type != TokenType::RSBracket {
+            fatal_error(error::LogoError::NotAexpression(
+                tokens[0].souce.clone(),
+                "not meet ]".to_string(),
+            ));
+        }
+        tokens[2..tokens.len() - 1].iter().map(word).collect()
+    } else {
+        let single = matches!(
+            tokens[1].token_type,
+            TokenType::Variable | TokenType::Float(_) | TokenType::Procedure
+        );
+        if tokens.len() > 2 || !single {
+            let rest: Vec<String> = tokens[1..].iter().map(word).collect();
+            fatal_error(error::LogoError::NotAexpression(
+                tokens[0].souce.clone(),
+                rest.join(" "),
+            ));
+        }
+        vec![word(&tokens[1])]
+    };
+    ASTNode::FunctionCall(name, Some(vec![words.join(" ")]))
+}
+
+// LSYSTEM "axiom [ rules ] iterations step angle
+fn lsystem_handling(tokens: &[Token]) -> ASTNode {
+    if tokens.len() < 4 || tokens[2].token_type != TokenType::LSBracket {
+        fatal_error(error::LogoError::NoEnoughArguments(tokens[0].souce.clone()));
+    }
+    let Some(close) = tokens
+        .iter()
+        .position(|token| token.token_type == TokenType::RSBracket)
+    else {
+        fatal_error(error::LogoError::NotAexpression(
+            tokens[0].souce.clone(),
+            "not meet ]".to_string(),
+        ));
+        unreachable!();
+    };
+
+    let rules: Vec<&str> = tokens[3..close]
+        .iter()
+        .map(|token| token.souce.as_str())
+        .collect();
+    let mut joined_string = String::new();
+    for (i, token) in tokens.iter().enumerate().skip(close + 1) {
+        if let Some(str) = is_expression(token) {
+            if i != close + 1 {
+                joined_string.push(' ');
+            }
+            joined_string.push_str(str);
+        } else {
+            fatal_error(error::LogoError::NotAexpression(
+                tokens[0].souce.clone(),
+                token.souce.clone(),
+            ));
+        }
+    }
+    ASTNode::FunctionCall(
+        FunName::LSystem,
+        Some(vec![
+            tokens[1].souce.clone(),
+            rules.join(" "),
+            joined_string,
+        ]),
+    )
+}
+
+fn plus_and_handling(tokens: &[Token]) -> ASTNode {
     if tokens.len() < 2 {
         fatal_error(error::LogoError::NoEnoughArguments(tokens[0].souce.clone()));
     }
@@ -50,8 +135,8 @@ fn plus_and_handling(tokens: &Vec<Token>) -> ASTNode {
     }
     let mut joined_string = String::new();
 
-    for i in 2..tokens.len() {
-        if let Some(str) = is_expression(&tokens[i]) {
+    for (i, token) in tokens.iter().enumerate().skip(2) {
+        if let Some(str) = is_expression(token) {
             if i != 2 {
                 joined_string.push(' ');
             }
@@ -63,12 +148,12 @@ fn plus_and_handling(tokens: &Vec<Token>) -> ASTNode {
     ASTNode::PlusAnd(tokens[1].souce.to_string(), joined_string)
 }
 
-fn define_handing(tokens: &Vec<Token>) -> ASTNode {
+fn define_handing(tokens: &[Token]) -> ASTNode {
     assert!(tokens.len() >= 2 && tokens[1].token_type == TokenType::Variable);
     let mut joined_string = String::new();
 
-    for i in 2..tokens.len() {
-        if let Some(str) = is_expression(&tokens[i]) {
+    for (i, token) in tokens.iter().enumerate().skip(2) {
+        if let Some(str) = is_expression(token) {
             if i != 2 {
                 joined_string.push(' ');
             }
@@ -127,7 +212,13 @@ where
         }
     }
 
+    // every statement remembers the source line it starts on
     fn handle_token(&mut self, tokens: Vec<Token>) -> ASTNode {
+        let line = self.token_source.get_current_line_number();
+        ASTNode::Line(line, Box::new(self.handle_statement(tokens)))
+    }
+
+    fn handle_statement(&mut self, tokens: Vec<Token>) -> ASTNode {
         match &tokens[0].token_type {
             TokenType::Invalid => {
                 // maybe user defined function
@@ -143,8 +234,8 @@ where
                         self.handle_user_defined_fn_call(&tokens[0].souce, &argument_size, None)
                     } else {
                         let mut joined_string = String::new();
-                        for i in 1..tokens.len() {
-                            if let Some(str) = is_expression(&tokens[i]) {
+                        for (i, token) in tokens.iter().enumerate().skip(1) {
+                            if let Some(str) = is_expression(token) {
                                 if i != 1 {
                                     joined_string.push(' ');
                                 }
@@ -179,6 +270,12 @@ where
                     }
                     ASTNode::FunctionCall(FunName::PenDown, None)
                 }
+                lexer::keyword::Keyword::PENERASE => {
+                    self.no_argument_handling(FunName::PenErase, &tokens)
+                }
+                lexer::keyword::Keyword::PENREVERSE => {
+                    self.no_argument_handling(FunName::PenReverse, &tokens)
+                }
                 lexer::keyword::Keyword::FORWARD => sequence_handing(FunName::Foreward, &tokens),
                 lexer::keyword::Keyword::BACK => sequence_handing(FunName::Back, &tokens),
                 lexer::keyword::Keyword::LEFT => sequence_handing(FunName::Left, &tokens),
@@ -192,6 +289,45 @@ where
                 }
                 lexer::keyword::Keyword::SETX => sequence_handing(FunName::SetXCoordinate, &tokens),
                 lexer::keyword::Keyword::SETY => sequence_handing(FunName::SetYCoordinate, &tokens),
+                lexer::keyword::Keyword::SETXY => sequence_handing(FunName::SetPosition, &tokens),
+                lexer::keyword::Keyword::SETPOS => {
+                    sequence_handing(FunName::SetPositionList, &strip_list(tokens))
+                }
+                lexer::keyword::Keyword::SETWINDOW => sequence_handing(FunName::SetWindow, &tokens),
+                lexer::keyword::Keyword::ARC => sequence_handing(FunName::Arc, &tokens),
+                lexer::keyword::Keyword::CIRCLE => sequence_handing(FunName::Circle, &tokens),
+                lexer::keyword::Keyword::BEZIER => sequence_handing(FunName::Bezier, &tokens),
+                lexer::keyword::Keyword::LABEL => words_handling(FunName::Label, &tokens),
+                lexer::keyword::Keyword::SETLABELFONT => {
+                    words_handling(FunName::SetLabelFont, &tokens)
+                }
+                lexer::keyword::Keyword::SETLABELSIZE => {
+                    sequence_handing(FunName::SetLabelSize, &tokens)
+                }
+                lexer::keyword::Keyword::TELL => sequence_handing(FunName::Tell, &tokens),
+                lexer::keyword::Keyword::ASK => self.parse_ask(&tokens),
+                lexer::keyword::Keyword::LSYSTEM => lsystem_handling(&tokens),
+                lexer::keyword::Keyword::SHOWTURTLE => {
+                    self.no_argument_handling(FunName::ShowTurtle, &tokens)
+                }
+                lexer::keyword::Keyword::HIDETURTLE => {
+                    self.no_argument_handling(FunName::HideTurtle, &tokens)
+                }
+                lexer::keyword::Keyword::SETSHAPE => words_handling(FunName::SetShape, &tokens),
+                lexer::keyword::Keyword::PUSHTURTLE => {
+                    self.no_argument_handling(FunName::PushTurtle, &tokens)
+                }
+                lexer::keyword::Keyword::POPTURTLE => {
+                    self.no_argument_handling(FunName::PopTurtle, &tokens)
+                }
+                lexer::keyword::Keyword::WRAP => self.no_argument_handling(FunName::Wrap, &tokens),
+                lexer::keyword::Keyword::FENCE => {
+                    self.no_argument_handling(FunName::Fence, &tokens)
+                }
+                lexer::keyword::Keyword::WINDOW => {
+                    self.no_argument_handling(FunName::Window, &tokens)
+                }
+                lexer::keyword::Keyword::WAIT => sequence_handing(FunName::Wait, &tokens),
 
                 lexer::keyword::Keyword::MAKE => define_handing(&tokens),
                 lexer::keyword::Keyword::ADDASSIGN => plus_and_handling(&tokens),
@@ -265,8 +401,9 @@ where
                         && tokens[0].token_type
                             == TokenType::Keyword(lexer::keyword::Keyword::WHILE)
                 {
+                    let line = self.token_source.get_current_line_number();
                     if let Some(node) = self.parse_if_while(&tokens) {
-                        block.push(node);
+                        block.push(ASTNode::Line(line, Box::new(node)));
                     } else {
                         panic!("unvalid if or while");
                     }
@@ -286,7 +423,7 @@ where
         block
     }
 
-    pub fn parse_if_while(&mut self, tokens: &Vec<Token>) -> Option<ASTNode> {
+    pub fn parse_if_while(&mut self, tokens: &[Token]) -> Option<ASTNode> {
         if tokens[tokens.len() - 1].token_type != TokenType::LSBracket {
             fatal_error(error::LogoError::UnvalidIfOrWhile(
                 self.token_source.get_current_line_number(),
@@ -296,13 +433,13 @@ where
         }
         // assert!()
         if tokens[0].token_type == TokenType::Keyword(lexer::keyword::Keyword::IF) {
-            let expression = Self::parse_expression(&tokens);
+            let expression = Self::parse_expression(tokens);
             // 继续解析条件执行的语句块，可能包含内部的 IF 和 WHILE
             let block = self.parse_block();
             Some(ASTNode::If(Box::new(expression), block))
         } else if tokens[0].token_type == TokenType::Keyword(lexer::keyword::Keyword::WHILE) {
             // 解析 WHILE 语句
-            let expression = Self::parse_expression(&tokens);
+            let expression = Self::parse_expression(tokens);
             // 继续解析条件循环执行的语句块，可能包含内部的 IF 和 WHILE
             let block = self.parse_block();
             Some(ASTNode::While(Box::new(expression), block))
@@ -312,11 +449,33 @@ where
         }
     }
 
-    fn parse_expression(tokens: &Vec<Token>) -> ASTNode {
+    fn no_argument_handling(&self, name: FunName, tokens: &[Token]) -> ASTNode {
+        if tokens.len() != 1 {
+            error::fatal_error(error::LogoError::TooManyArguments(
+                self.token_source.get_current_line_number(),
+                tokens[0].souce.clone(),
+            ));
+        }
+        ASTNode::FunctionCall(name, None)
+    }
+
+    fn parse_ask(&mut self, tokens: &[Token]) -> ASTNode {
+        if tokens.len() < 3 || tokens[tokens.len() - 1].token_type != TokenType::LSBracket {
+            fatal_error(error::LogoError::UnvalidIfOrWhile(
+                self.token_source.get_current_line_number(),
+                "ASK not meet [".to_string(),
+            ));
+        }
+        let expression = Self::parse_expression(tokens);
+        let block = self.parse_block();
+        ASTNode::Ask(Box::new(expression), block)
+    }
+
+    fn parse_expression(tokens: &[Token]) -> ASTNode {
         let mut joined_string = String::new();
 
-        for i in 1..tokens.len() - 1 {
-            if let Some(str) = is_expression(&tokens[i]) {
+        for (i, token) in tokens.iter().enumerate().take(tokens.len() - 1).skip(1) {
+            if let Some(str) = is_expression(token) {
                 if i != 0 {
                     joined_string.push(' ');
                 }
@@ -338,18 +497,16 @@ where
         ASTNode::CustomFunction(name.to_owned(), expression)
     }
 
-    pub fn parse_function(&mut self, tokens: &Vec<Token>) -> ASTNode {
+    pub fn parse_function(&mut self, tokens: &[Token]) -> ASTNode {
         assert!(tokens.len() >= 2);
 
         let mut block = Vec::new();
-        let mut size = 0;
         let mut vars_name = Vec::new();
-        for idx in 2..tokens.len() {
-            if tokens[idx].token_type != TokenType::Variable {
+        for token in tokens.iter().skip(2) {
+            if token.token_type != TokenType::Variable {
                 panic!("argumentnot a variable fine");
             } else {
-                vars_name.push(tokens[idx].souce.clone());
-                size = size + 1;
+                vars_name.push(token.souce.clone());
             }
         }
 
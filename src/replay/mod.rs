@@ -0,0 +1,210 @@
+mod json;
+
+use crate::error::LogoError;
+use crate::executor::{Coordinates, EdgeMode, Executor, TurtleShape};
+use json::Json;
+
+// first line of an emitted trace, the remaining lines are json trace events
+pub fn header(width: u32, height: u32, coordinates: Coordinates) -> String {
+    format!(
+        "{{\"width\":{width},\"height\":{height},\"coordinates\":\"{}\"}}",
+        coordinates.name()
+    )
+}
+
+struct Event {
+    // line in the trace file, for error messages
+    at: usize,
+    // line of the logo program that issued the command
+    line: usize,
+    command: String,
+    args: Vec<Json>,
+}
+
+// executor calls read back from a json trace
+pub struct Trace {
+    size: Option<(u32, u32)>,
+    // older traces do not say, they were all in canvas pixels
+    coordinates: Coordinates,
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn load(path: &std::path::Path) -> Result<Self, LogoError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LogoError::InvalidTrace(0, format!("{}: {e}", path.display())))?;
+
+        let mut size = None;
+        let mut coordinates = Coordinates::Canvas;
+        let mut events = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let at = i + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value = json::parse(line).map_err(|e| LogoError::InvalidTrace(at, e))?;
+
+            let Some(command) = value.get("command").and_then(Json::as_str) else {
+                let dimension = |key| {
+                    value
+                        .get(key)
+                        .and_then(Json::as_number)
+                        .filter(|v| *v >= 1.0 && v.fract() == 0.0)
+                        .map(|v| v as u32)
+                };
+                match (events.is_empty(), dimension("width"), dimension("height")) {
+                    (true, Some(width), Some(height)) => size = Some((width, height)),
+                    _ => return Err(LogoError::InvalidTrace(at, "not a trace event".to_string())),
+                }
+                if let Some(name) = value.get("coordinates").and_then(Json::as_str) {
+                    coordinates = clap::ValueEnum::from_str(name, false).map_err(|_| {
+                        LogoError::InvalidTrace(at, format!("unknown coordinates {name}"))
+                    })?;
+                }
+                continue;
+            };
+            let args = match value.get("args") {
+                Some(Json::Array(args)) => args.clone(),
+                _ => return Err(LogoError::InvalidTrace(at, "missing args".to_string())),
+            };
+            let line = value
+                .get("line")
+                .and_then(Json::as_number)
+                .map_or(0, |v| v as usize);
+            events.push(Event {
+                at,
+                line,
+                command: command.to_string(),
+                args,
+            });
+        }
+
+        Ok(Trace {
+            size,
+            coordinates,
+            events,
+        })
+    }
+
+    // canvas size the trace was recorded on, when it says
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.size
+    }
+
+    // runs every recorded call again, in order, on `executor`
+    pub fn replay(&self, executor: &mut Box<dyn Executor>) -> Result<(), LogoError> {
+        executor.set_coordinates(self.coordinates);
+        for event in &self.events {
+            executor.set_source_line(event.line);
+            event.apply(executor)?;
+        }
+        Ok(())
+    }
+}
+
+impl Event {
+    fn invalid(&self, reason: String) -> LogoError {
+        LogoError::InvalidTrace(self.at, reason)
+    }
+
+    fn numbers(&self, count: usize) -> Result<Vec<f32>, LogoError> {
+        let numbers: Option<Vec<f32>> = self.args.iter().map(Json::as_number).collect();
+        match numbers {
+            Some(numbers) if numbers.len() == count => Ok(numbers),
+            _ => Err(self.invalid(format!("{} expects {count} numbers", self.command))),
+        }
+    }
+
+    fn number(&self) -> Result<f32, LogoError> {
+        Ok(self.numbers(1)?[0])
+    }
+
+    // non-negative whole number, like colours and turtle ids
+    fn index(&self) -> Result<u32, LogoError> {
+        let v = self.number()?;
+        if v < 0.0 || v.fract() != 0.0 {
+            return Err(self.invalid(format!("{} expects a whole number, not {v}", self.command)));
+        }
+        Ok(v as u32)
+    }
+
+    fn word(&self) -> Result<&str, LogoError> {
+        match self.args.as_slice() {
+            [Json::String(word)] => Ok(word),
+            _ => Err(self.invalid(format!("{} expects one word", self.command))),
+        }
+    }
+
+    fn apply(&self, executor: &mut Box<dyn Executor>) -> Result<(), LogoError> {
+        match self.command.as_str() {
+            "PENUP" => executor.pen_up(),
+            "PENDOWN" => executor.pen_down(),
+            "PENERASE" => executor.pen_erase(),
+            "PENREVERSE" => executor.pen_reverse(),
+            "FORWARD" => executor.foreward(self.number()?)?,
+            "BACK" => executor.back(self.number()?)?,
+            "LEFT" => executor.left(self.number()?)?,
+            "RIGHT" => executor.right(self.number()?)?,
+            "JUMP" => executor.jump(self.number()?)?,
+            "SETPENCOLOR" => {
+                let color = self.index()?;
+                if color > 15 {
+                    return Err(self.invalid(format!("colour {color} is not between 0 and 15")));
+                }
+                executor.set_color(color);
+            }
+            "TURN" => executor.turn(self.number()?),
+            "SETHEADING" => executor.set_heading(self.number()?),
+            "SETX" => executor.set_x_coordinate(self.number()?)?,
+            "SETY" => executor.set_y_coordinate(self.number()?)?,
+            "SETXY" => {
+                let v = self.numbers(2)?;
+                executor.set_position(v[0], v[1])?;
+            }
+            "SETWINDOW" => {
+                let v = self.numbers(4)?;
+                executor.set_window((v[0], v[1]), (v[2], v[3]))?;
+            }
+            "WRAP" => executor.set_edge_mode(EdgeMode::Wrap),
+            "FENCE" => executor.set_edge_mode(EdgeMode::Fence),
+            "WINDOW" => executor.set_edge_mode(EdgeMode::Window),
+            "ARC" => {
+                let v = self.numbers(2)?;
+                executor.arc(v[0], v[1]);
+            }
+            "CIRCLE" => executor.circle(self.number()?),
+            "BEZIER" => {
+                let v = self.numbers(6)?;
+                executor.bezier((v[0], v[1]), (v[2], v[3]), (v[4], v[5]))?;
+            }
+            "LABEL" => executor.label(self.word()?),
+            "SETLABELFONT" => executor.set_label_font(self.word()?),
+            "SETLABELSIZE" => executor.set_label_size(self.number()?),
+            "WAIT" => executor.wait(self.number()?),
+            "GROUP" => executor.begin_group(self.word()?),
+            "ENDGROUP" => executor.end_group(),
+            "TELL" => executor.tell(self.index()?),
+            "PUSHTURTLE" => executor.push_turtle(),
+            "POPTURTLE" => executor.pop_turtle()?,
+            "SHOWTURTLE" => executor.show_turtle(),
+            "HIDETURTLE" => executor.hide_turtle(),
+            "SETSHAPE" => {
+                let words: Vec<String> = self
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        Json::String(word) => word.clone(),
+                        Json::Number(v) => v.to_string(),
+                        _ => String::new(),
+                    })
+                    .collect();
+                let words = words.join(" ");
+                let shape = TurtleShape::from_words(&words)
+                    .ok_or_else(|| self.invalid(format!("unknown turtle shape {words}")))?;
+                executor.set_shape(shape);
+            }
+            command => return Err(self.invalid(format!("unknown command {command}"))),
+        }
+        Ok(())
+    }
+}
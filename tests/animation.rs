@@ -0,0 +1,47 @@
+mod common;
+
+// three sides of a square with half a second's pause before the last
+const PROGRAM: &str = "PENDOWN\nFORWARD \"10\nTURN \"90\nFORWARD \"10\n\
+                       WAIT \"30\nTURN \"90\nFORWARD \"10\n";
+
+#[test]
+fn svg_segments_appear_in_order() {
+    let dir = common::fresh_dir("animation/svg");
+    let image = dir.join("square.svg");
+    let program = common::program(&dir, "square", PROGRAM);
+    common::draw(&program, &image, [100, 100], &["--animate", "--fps", "4"]);
+    let svg = common::read(&image);
+    let begins: Vec<&str> = svg
+        .split("begin=\"")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('"').expect("begin end")])
+        .collect();
+    // a frame a quarter second, the wait holds the second frame two more
+    assert_eq!(begins, ["0s", "0.25s", "1s"], "{svg}");
+}
+
+#[test]
+fn segments_per_frame_groups_segments() {
+    let dir = common::fresh_dir("animation/grouped");
+    let image = dir.join("square.svg");
+    let program = common::program(&dir, "square", PROGRAM);
+    let flags = ["--animate", "--segments-per-frame", "2"];
+    common::draw(&program, &image, [100, 100], &flags);
+    let svg = common::read(&image);
+    assert_eq!(svg.matches("<set ").count(), 2, "{svg}");
+}
+
+#[test]
+fn png_frames_are_numbered() {
+    let dir = common::fresh_dir("animation/png");
+    let image = dir.join("square.png");
+    let program = common::program(&dir, "square", PROGRAM);
+    common::draw(&program, &image, [100, 100], &["--animate", "--fps", "4"]);
+    let frame = |n: usize| std::fs::read(dir.join(format!("square-{n:04}.png")));
+
+    assert!(!image.exists());
+    // the wait repeats the second frame
+    assert_eq!(frame(2).expect("frame 2"), frame(4).expect("frame 4"));
+    assert_ne!(frame(4).expect("frame 4"), frame(5).expect("frame 5"));
+    assert!(frame(6).is_err());
+}
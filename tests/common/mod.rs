@@ -0,0 +1,159 @@
+// helpers shared by the integration tests, each test binary uses only some
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+pub fn rslogo() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_rslogo"))
+}
+
+// a program under logo_examples/, by name without the extension
+pub fn example(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("logo_examples")
+        .join(format!("{name}.lg"))
+}
+
+// directory for what a test writes; test binaries run at once, so every
+// test file keeps to names of its own
+pub fn out_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::create_dir_all(&dir).expect("output directory");
+    dir
+}
+
+// an empty `out_dir`, for tests that look at every file written
+pub fn fresh_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    out_dir(name)
+}
+
+// names of the files in `dir`, sorted
+pub fn files_in(dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("{}: {e}", dir.display()))
+        .map(|entry| {
+            let entry = entry.expect("directory entry");
+            entry.file_name().to_string_lossy().into_owned()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+// writes `source` to `name`.lg in `dir`
+pub fn program(dir: &Path, name: &str, source: &str) -> PathBuf {
+    let program = dir.join(format!("{name}.lg"));
+    std::fs::write(&program, source).expect("program");
+    program
+}
+
+// runs `program` on a canvas `size` high and wide, whatever its outcome
+pub fn run(program: &Path, image: &Path, size: [u32; 2], flags: &[&str]) -> Output {
+    rslogo()
+        .arg(program)
+        .arg(image)
+        .args(size.map(|v| v.to_string()))
+        .args(flags)
+        .output()
+        .expect("run rslogo")
+}
+
+// runs `source` as `name`.lg on a 200 x 200 canvas, giving the outcome and
+// the svg written to `name`.svg, empty when there is none
+pub fn run_source(dir: &str, name: &str, source: &str) -> (Output, String) {
+    let dir = out_dir(dir);
+    let program = program(&dir, name, source);
+    let image = dir.join(format!("{name}.svg"));
+    let _ = std::fs::remove_file(&image);
+    let output = run(&program, &image, [200, 200], &[]);
+    let svg = std::fs::read_to_string(&image).unwrap_or_default();
+    (output, svg)
+}
+
+// like `run`, for programs that have to succeed
+pub fn draw(program: &Path, image: &Path, size: [u32; 2], flags: &[&str]) -> Output {
+    let output = run(program, image, size, flags);
+    assert!(
+        output.status.success(),
+        "{}: {}",
+        program.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+// like `run_source`, for programs that have to succeed
+pub fn draw_source(dir: &str, name: &str, source: &str) -> String {
+    let (output, svg) = run_source(dir, name, source);
+    assert!(
+        output.status.success(),
+        "{name}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    svg
+}
+
+pub fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+// every straight stroke of an svg drawing, from its polylines and from the
+// move and line commands of its paths, with its points and colour
+pub fn polylines(svg: &str) -> Vec<(Vec<(f32, f32)>, String)> {
+    let number = |v: &str| v.parse::<f32>().expect("coordinate");
+    let mut strokes = Vec::new();
+    for line in svg.lines() {
+        let Some(color) = line.split(" stroke=\"").nth(1) else {
+            continue;
+        };
+        let color = color[..color.find('"').expect("colour end")].to_string();
+        if let Some(points) = line.strip_prefix("<polyline points=\"") {
+            let points = points[..points.find('"').expect("points end")]
+                .split(' ')
+                .map(|point| {
+                    let (x, y) = point.split_once(',').expect("x,y");
+                    (number(x), number(y))
+                })
+                .collect();
+            strokes.push((points, color));
+        } else if let Some(d) = line.strip_prefix("<path d=\"") {
+            let d = &d[..d.find('"').expect("path end")];
+            // arcs and curves are not straight strokes
+            if d.contains(['A', 'C']) {
+                continue;
+            }
+            for sub_path in d.split("M ").skip(1) {
+                let numbers: Vec<f32> = sub_path
+                    .split_whitespace()
+                    .filter(|v| *v != "L")
+                    .map(number)
+                    .collect();
+                let points = numbers.chunks(2).map(|p| (p[0], p[1])).collect();
+                strokes.push((points, color.clone()));
+            }
+        }
+    }
+    strokes
+}
+
+// `polylines` rounded to whole pixels
+pub fn strokes(svg: &str) -> Vec<(Vec<[i32; 2]>, String)> {
+    polylines(svg)
+        .into_iter()
+        .map(|(points, color)| {
+            let points = points
+                .iter()
+                .map(|(x, y)| [x.round() as i32, y.round() as i32])
+                .collect();
+            (points, color)
+        })
+        .collect()
+}
+
+// just the points of every straight stroke, rounded to whole pixels
+pub fn points(svg: &str) -> Vec<Vec<[i32; 2]>> {
+    strokes(svg).into_iter().map(|(points, _)| points).collect()
+}
@@ -0,0 +1,93 @@
+mod common;
+
+// points of each straight stroke
+fn polylines(svg: &str) -> Vec<Vec<(f32, f32)>> {
+    common::polylines(svg)
+        .into_iter()
+        .map(|(points, _)| points)
+        .collect()
+}
+
+// the canvases here are 200 wide and 100 high
+#[test]
+fn canvas_coordinates_are_the_default() {
+    let dir = common::out_dir("coordinates");
+    let program = common::program(&dir, "canvas", "PENDOWN\nSETXY \"150 \"20\nSETY YCOR\n");
+    common::draw(&program, &dir.join("canvas.svg"), [100, 200], &[]);
+    let svg = common::read(&dir.join("canvas.svg"));
+    assert_eq!(polylines(&svg), [[(100.0, 50.0), (150.0, 20.0)]]);
+}
+
+#[test]
+fn centre_coordinates_point_y_up() {
+    // XCOR and YCOR read back what SETX and SETY set
+    let source = "PENDOWN\nSETXY \"50 \"30\nSETX \"0\nIF EQ XCOR \"0 [\n  SETY \"-40\n]\n\
+                  IF EQ YCOR \"-40 [\n  SETXY \"-100 \"0\n]\n";
+    let dir = common::out_dir("coordinates");
+    let program = common::program(&dir, "centre", source);
+    let flags = ["--coordinates", "centre"];
+    common::draw(&program, &dir.join("centre.svg"), [100, 200], &flags);
+    let svg = common::read(&dir.join("centre.svg"));
+    // SETX and SETY move without drawing
+    assert!(
+        svg.contains("<path d=\"M 100 50 L 150 20 M 100 90 L 0 50\""),
+        "{svg}"
+    );
+}
+
+#[test]
+fn setwindow_maps_world_units_onto_the_canvas() {
+    // the square window fits the height, 50 pixels to the unit
+    let source = "SETWINDOW \"-1 \"-1 \"1 \"1\nPENDOWN\nSETXY \"-1 \"1\nFORWARD \"0.5\n\
+                  IF EQ YCOR \"1.5 [\n  SETXY \"1 \"-1\n]\n";
+    let dir = common::out_dir("coordinates");
+    let program = common::program(&dir, "window", source);
+    let flags = ["--coordinates", "centre"];
+    common::draw(&program, &dir.join("window.svg"), [100, 200], &flags);
+    let svg = common::read(&dir.join("window.svg"));
+    assert_eq!(
+        polylines(&svg),
+        [[(100.0, 50.0), (50.0, 0.0), (50.0, -25.0), (150.0, 100.0)]],
+        "{svg}"
+    );
+
+    // without centred coordinates the window's top is its ymin
+    let source = "SETWINDOW \"0 \"0 \"4 \"2\nPENDOWN\nSETXY \"0 \"0\n";
+    let program = common::program(&dir, "window-canvas", source);
+    common::draw(&program, &dir.join("window-canvas.svg"), [100, 200], &[]);
+    let svg = common::read(&dir.join("window-canvas.svg"));
+    assert_eq!(polylines(&svg), [[(100.0, 50.0), (0.0, 0.0)]]);
+}
+
+#[test]
+fn empty_window_is_an_error() {
+    let dir = common::out_dir("coordinates");
+    let program = common::program(&dir, "empty-window", "SETWINDOW \"0 \"0 \"0 \"10\n");
+    let output = common::run(&program, &dir.join("empty-window.svg"), [100, 200], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("empty window"));
+}
+
+#[test]
+fn replay_keeps_the_coordinates_of_the_run() {
+    let source = "SETWINDOW \"-10 \"-10 \"10 \"10\nPENDOWN\nSETXY \"5 \"5\nARC \"90 \"5\n";
+    let dir = common::out_dir("coordinates");
+    let program = common::program(&dir, "replayed", source);
+    let trace = dir.join("replayed.jsonl");
+    let trace_arg = trace.to_str().expect("path");
+    let flags = ["--coordinates", "centre", "--emit-trace", trace_arg];
+    common::draw(&program, &dir.join("replayed.svg"), [100, 200], &flags);
+
+    let image = dir.join("replay.svg");
+    let status = common::rslogo()
+        .arg("replay")
+        .arg(&trace)
+        .arg(&image)
+        .status()
+        .expect("run rslogo");
+    assert!(status.success());
+    assert_eq!(
+        common::read(&image),
+        common::read(&dir.join("replayed.svg"))
+    );
+}
@@ -0,0 +1,26 @@
+mod common;
+
+#[test]
+fn curves_stay_curves() {
+    let image = common::out_dir("curves").join("curves.svg");
+    common::draw(&common::example("6_02_curves"), &image, [200, 200], &[]);
+    let svg = common::read(&image);
+    assert!(
+        svg.contains("d=\"M 100 60 A 40 40 0 0 1 140 100\""),
+        "{svg}"
+    );
+    // the three quarter arc takes the long way round
+    assert!(
+        svg.contains("d=\"M 100 130 A 30 30 0 1 1 130 100\""),
+        "{svg}"
+    );
+    assert!(
+        svg.contains("<circle cx=\"100\" cy=\"100\" r=\"10\""),
+        "{svg}"
+    );
+    assert!(
+        svg.contains("d=\"M 20 150 C 60 100 140 200 180 150\""),
+        "{svg}"
+    );
+    assert!(common::strokes(&svg).is_empty(), "{svg}");
+}
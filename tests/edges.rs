@@ -0,0 +1,94 @@
+mod common;
+
+#[test]
+fn wrap_carries_on_from_the_opposite_edge() {
+    // XCOR reads the wrapped position back
+    let source = "WRAP\nPENDOWN\nTURN \"90\nFORWARD \"250\nIF EQ XCOR \"150 [\n  BACK \"10\n]\n";
+    let svg = common::draw_source("edges", "wrap-right", source);
+    assert_eq!(
+        common::points(&svg),
+        vec![
+            vec![[100, 100], [200, 100]],
+            vec![[0, 100], [150, 100], [140, 100]],
+        ]
+    );
+}
+
+#[test]
+fn wrap_splits_a_move_at_every_edge_it_crosses() {
+    // twice across the top edge
+    let svg = common::draw_source("edges", "wrap-twice", "WRAP\nPENDOWN\nFORWARD \"450\n");
+    assert_eq!(
+        common::points(&svg),
+        vec![
+            vec![[100, 100], [100, 0]],
+            vec![[100, 200], [100, 0]],
+            vec![[100, 200], [100, 50]],
+        ]
+    );
+
+    // through the top right corner into the bottom left one
+    let source = "WRAP\nPENDOWN\nSETHEADING \"45\nFORWARD \"300\n";
+    let svg = common::draw_source("edges", "wrap-corner", source);
+    assert_eq!(
+        common::points(&svg),
+        vec![vec![[100, 100], [200, 0]], vec![[0, 200], [112, 88]]]
+    );
+}
+
+#[test]
+fn long_wrapped_moves_finish() {
+    // without the pen there is nothing to split
+    let source = "WRAP\nFORWARD \"1000000000\nPENDOWN\nSETXY \"10 \"10\n";
+    let svg = common::draw_source("edges", "wrap-far-up", source);
+    assert_eq!(common::points(&svg).len(), 1);
+
+    // with it the move is cut short once the lines cover the canvas
+    let source = "WRAP\nPENDOWN\nSETHEADING \"30\nFORWARD \"1000000000\n";
+    let svg = common::draw_source("edges", "wrap-far-down", source);
+    let strokes = common::points(&svg).len();
+    assert!((1000..=10_001).contains(&strokes), "{strokes}");
+}
+
+#[test]
+fn fence_stops_at_the_edge() {
+    // reaching the edge is fine
+    common::draw_source("edges", "fence-edge", "FENCE\nPENDOWN\nFORWARD \"100\n");
+
+    let (output, _) = common::run_source("edges", "fence-out", "FENCE\nPENDOWN\nFORWARD \"150\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("outside (0, 0) to"), "{stderr}");
+
+    // positions read as the program gave them
+    let source = "SETWINDOW \"-10 \"-10 \"10 \"10\nFENCE\nSETXY \"5 \"5\nFORWARD \"6\n";
+    let program = common::program(&common::out_dir("edges"), "fence-world", source);
+    let image = common::out_dir("edges").join("fence-world.svg");
+    let output = common::run(&program, &image, [200, 200], &["--coordinates", "centre"]);
+    assert_eq!(output.status.code(), Some(1));
+    // the report wraps long lines
+    let stderr = String::from_utf8_lossy(&output.stderr).replace("\n  │ ", " ");
+    assert!(
+        stderr.contains("turtle at (5, 5) cannot move to (5, 11), outside (-10, -10) to (10, 10)"),
+        "{stderr}"
+    );
+
+    // jumps are fenced too
+    let (output, _) = common::run_source("edges", "fence-jump", "FENCE\nSETX \"-1\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn window_lets_the_turtle_leave_the_canvas() {
+    let source = "WINDOW\nPENDOWN\nFORWARD \"150\nBACK \"50\n";
+    let svg = common::draw_source("edges", "window", source);
+    assert_eq!(
+        common::points(&svg),
+        vec![vec![[100, 100], [100, -50], [100, 0]]]
+    );
+
+    // back under WRAP the next move starts from the wrapped position
+    let source = "WINDOW\nFORWARD \"150\nWRAP\nPENDOWN\nRIGHT \"10\n";
+    let svg = common::draw_source("edges", "window-wrap", source);
+    assert_eq!(common::points(&svg), vec![vec![[100, 150], [110, 150]]]);
+}
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+mod common;
+
+// every program in logo_examples/ is run headless and compared with the files
+// under tests/golden/: the drawing operations it records in <name>.ops, or for
+// *_err.lg programs the diagnostic it prints in <name>.err.
+// UPDATE_GOLDEN=1 cargo test rewrites the golden files from the current output.
+
+const WIDTH: &str = "200";
+const HEIGHT: &str = "200";
+
+fn examples() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("logo_examples");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .expect("logo_examples directory")
+        .map(|entry| entry.expect("directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lg"))
+        .collect();
+    files.sort();
+    files
+}
+
+// compares `actual` with the golden file, or rewrites it when asked to
+fn check(golden: &Path, actual: &str, failures: &mut Vec<String>) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(golden, actual).expect("write golden file");
+        return;
+    }
+    match std::fs::read_to_string(golden) {
+        Ok(expected) if expected == actual => {}
+        Ok(_) => failures.push(format!("{} differs", golden.display())),
+        Err(e) => failures.push(format!("{}: {e}", golden.display())),
+    }
+}
+
+#[test]
+fn examples_match_golden() {
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden");
+    let out = common::out_dir("golden");
+    std::fs::create_dir_all(&golden).expect("golden directory");
+
+    let mut failures = Vec::new();
+    for example in examples() {
+        let name = example.file_stem().unwrap().to_str().unwrap().to_string();
+        let ops = out.join(format!("{name}.ops"));
+        let output = common::rslogo()
+            .arg(&example)
+            .arg(out.join(format!("{name}.svg")))
+            .args([HEIGHT, WIDTH])
+            .arg("--record")
+            .arg(&ops)
+            .output()
+            .expect("run rslogo");
+
+        if name.ends_with("_err") {
+            if output.status.success() {
+                failures.push(format!("{name} should fail"));
+                continue;
+            }
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            check(&golden.join(format!("{name}.err")), &stderr, &mut failures);
+        } else {
+            if !output.status.success() {
+                failures.push(format!(
+                    "{name} failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+                continue;
+            }
+            let actual = std::fs::read_to_string(&ops).expect("recorded operations");
+            check(&golden.join(format!("{name}.ops")), &actual, &mut failures);
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+// replaying the trace of a run has to draw exactly what the run drew
+#[test]
+fn replay_matches_run() {
+    let out = common::out_dir("replay");
+
+    let mut failures = Vec::new();
+    for example in examples() {
+        let name = example.file_stem().unwrap().to_str().unwrap().to_string();
+        if name.ends_with("_err") {
+            continue;
+        }
+        let (image, trace, replayed) = (
+            out.join(format!("{name}.svg")),
+            out.join(format!("{name}.jsonl")),
+            out.join(format!("{name}.replay.svg")),
+        );
+
+        let run = common::rslogo()
+            .arg("run")
+            .arg(&example)
+            .arg(&image)
+            .args([HEIGHT, WIDTH])
+            .arg("--emit-trace")
+            .arg(&trace)
+            .status()
+            .expect("run rslogo");
+        let replay = common::rslogo()
+            .arg("replay")
+            .arg(&trace)
+            .arg(&replayed)
+            .status()
+            .expect("replay rslogo");
+        if !run.success() || !replay.success() {
+            failures.push(format!("{name} did not run and replay"));
+            continue;
+        }
+
+        let expected = common::read(&image);
+        let actual = common::read(&replayed);
+        if expected != actual {
+            failures.push(format!("{name} replays differently"));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
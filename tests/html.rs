@@ -0,0 +1,47 @@
+mod common;
+
+// [line, markup] entries of the player's steps
+fn steps(html: &str) -> Vec<usize> {
+    let start = html.find("const steps = [\n").expect("steps") + "const steps = [\n".len();
+    let end = start + html[start..].find("\n];").expect("steps end");
+    html[start..end]
+        .lines()
+        .map(|step| {
+            step[1..step.find(',').expect("line")]
+                .parse()
+                .expect("line number")
+        })
+        .collect()
+}
+
+#[test]
+fn steps_point_at_the_lines_that_drew_them() {
+    let dir = common::out_dir("html");
+    let source = "PENDOWN\nFORWARD \"10\n\nTURN \"90\nFORWARD \"10\nSETPENCOLOR \"2\nBACK \"5\n";
+    let program = common::program(&dir, "square", source);
+    common::draw(&program, &dir.join("square.html"), [100, 100], &[]);
+    let html = common::read(&dir.join("square.html"));
+    assert_eq!(steps(&html), [2, 5, 7]);
+    assert!(
+        html.contains("<span id=\"line-5\">FORWARD &quot;10</span>"),
+        "{html}"
+    );
+}
+
+#[test]
+fn page_is_self_contained() {
+    let dir = common::out_dir("html");
+    let source = "PENDOWN\n// a</script><b>\nFORWARD \"10\nLABEL \"a&b\n";
+    let program = common::program(&dir, "escaped", source);
+    common::draw(&program, &dir.join("escaped.html"), [100, 100], &[]);
+    let html = common::read(&dir.join("escaped.html"));
+    assert!(!html.contains("src=") && !html.contains("href="), "{html}");
+    // markup in the program is shown as text and cannot end the script early
+    assert!(
+        html.contains("<span id=\"line-2\">// a&lt;/script&gt;&lt;b&gt;</span>"),
+        "{html}"
+    );
+    assert_eq!(html.matches("</script>").count(), 1, "{html}");
+    assert!(html.contains("a&amp;b\\u003c/text>"), "{html}");
+    assert_eq!(steps(&html), [3, 4]);
+}
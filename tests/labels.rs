@@ -0,0 +1,39 @@
+mod common;
+
+#[test]
+fn labels_keep_their_font_size_and_heading() {
+    let image = common::out_dir("labels").join("labels.svg");
+    common::draw(&common::example("6_03_labels"), &image, [200, 200], &[]);
+    let svg = common::read(&image);
+    // heading 0 points up, so the first label reads bottom to top
+    assert!(
+        svg.contains("font-family=\"sans-serif\" font-size=\"12\" fill=\"#ffffff\" transform=\"rotate(-90 100 70)\">length</text>"),
+        "{svg}"
+    );
+    assert!(
+        svg.contains("font-family=\"monospace\" font-size=\"20\" fill=\"#ffffff\" transform=\"rotate(0 100 70)\">3.50</text>"),
+        "{svg}"
+    );
+    assert!(svg.contains(">side is 30 long</text>"), "{svg}");
+}
+
+#[test]
+fn undefined_variables_in_labels_are_errors() {
+    let output = common::run(
+        &common::example("6_04_label_undefined_err"),
+        &common::out_dir("labels").join("undefined.svg"),
+        [200, 200],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(":width"), "{stderr}");
+}
+
+#[test]
+fn label_sizes_are_in_world_units() {
+    // 100 pixels to the unit
+    let source = "SETWINDOW \"-1 \"-1 \"1 \"1\nSETLABELSIZE \"0.2\nLABEL \"big\n";
+    let svg = common::draw_source("labels", "window-size", source);
+    assert!(svg.contains(" font-size=\"20\" "), "{svg}");
+}
@@ -0,0 +1,63 @@
+mod common;
+
+#[test]
+fn lsystems_draw_dashes_and_branches() {
+    let image = common::out_dir("lsystem").join("lsystem.svg");
+    common::draw(&common::example("6_10_lsystem"), &image, [200, 200], &[]);
+    assert_eq!(
+        common::points(&common::read(&image)),
+        vec![
+            // f leaves a gap between the dashes
+            vec![[100, 100], [100, 96]],
+            vec![[100, 92], [100, 88]],
+            vec![[100, 84], [100, 80]],
+            vec![[100, 76], [100, 72]],
+            // both branches start from the top of the stem
+            vec![[60, 150], [60, 140], [60, 130], [55, 121]],
+            vec![[60, 130], [65, 121]],
+        ]
+    );
+}
+
+#[test]
+fn invalid_lsystems_are_errors() {
+    let dir = common::out_dir("lsystem");
+    for (name, reason) in [
+        ("6_11_lsystem_iterations_err", "iterations 1.5"),
+        ("6_12_lsystem_rule_err", "rule FF+F"),
+    ] {
+        let image = dir.join(format!("{name}.svg"));
+        let output = common::run(&common::example(name), &image, [200, 200], &[]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("invalid LSYSTEM: {reason}")),
+            "{stderr}"
+        );
+    }
+}
+
+#[test]
+fn branches_must_close() {
+    // the POPTURTLE a stray ] would do belongs to the program
+    for (name, source, reason) in [
+        (
+            "open-rule",
+            "LSYSTEM \"F [ F=F[+F ] \"1 \"10 \"90\n",
+            "brackets in F=F[+F",
+        ),
+        (
+            "early-close",
+            "LSYSTEM \"F]F[ [ ] \"1 \"10 \"90\n",
+            "brackets in F]F[",
+        ),
+    ] {
+        let (output, _) = common::run_source("lsystem", name, source);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("invalid LSYSTEM: {reason}")),
+            "{stderr}"
+        );
+    }
+}
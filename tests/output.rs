@@ -0,0 +1,102 @@
+mod common;
+
+use common::{example, fresh_dir};
+
+#[test]
+fn unsupported_extension_fails_before_running() {
+    let dir = fresh_dir("unsupported");
+    // the program would fail as well, the image is checked first
+    let output = common::run(
+        &example("2_03_missing_var_err"),
+        &dir.join("image.jpg"),
+        [100, 100],
+        &[],
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("image file extension not supported"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn save_failure_exits_non_zero() {
+    let dir = fresh_dir("unwritable");
+    let output = common::run(
+        &example("1_00_penup_pendown"),
+        &dir.join("missing").join("image.svg"),
+        [100, 100],
+        &[],
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("could not save image"), "{stderr}");
+}
+
+#[test]
+fn saving_leaves_only_the_image() {
+    let dir = fresh_dir("atomic");
+    for image in ["image.svg", "image.png"] {
+        common::draw(
+            &example("1_00_penup_pendown"),
+            &dir.join(image),
+            [100, 100],
+            &[],
+        );
+    }
+    assert_eq!(common::files_in(&dir), ["image.png", "image.svg"]);
+}
+
+#[test]
+fn keep_partial_saves_up_to_the_error() {
+    let dir = fresh_dir("partial");
+    let image = dir.join("image.svg");
+    let output = common::run(
+        &example("2_03_missing_var_err"),
+        &image,
+        [100, 100],
+        &["--keep-partial", "--mark-stop"],
+    );
+
+    // still an error, but with the drawing so far
+    assert!(!output.status.success());
+    let svg = common::read(&image);
+    assert!(svg.contains(">line 5</text>"), "{svg}");
+}
+
+#[test]
+fn errors_save_nothing_by_default() {
+    let dir = fresh_dir("no_partial");
+    let image = dir.join("image.svg");
+    let output = common::run(&example("2_03_missing_var_err"), &image, [100, 100], &[]);
+
+    assert!(!output.status.success());
+    assert!(!image.exists());
+}
+
+#[test]
+fn split_colors_writes_a_file_per_pen() {
+    let dir = fresh_dir("split");
+    common::draw(
+        &example("5_00_boxes"),
+        &dir.join("boxes.gcode"),
+        [100, 100],
+        &["--split-colors"],
+    );
+    assert_eq!(
+        common::files_in(&dir),
+        [
+            "boxes-10.gcode",
+            "boxes-4.gcode",
+            "boxes-6.gcode",
+            "boxes-8.gcode",
+            "boxes-9.gcode"
+        ]
+    );
+    // a single pen needs no pen changes
+    let red = common::read(&dir.join("boxes-4.gcode"));
+    assert!(!red.contains("M0 ;"), "{red}");
+}
@@ -0,0 +1,36 @@
+mod common;
+
+#[test]
+fn pen_modes_erase_and_invert() {
+    let image = common::out_dir("pens").join("pen-modes.svg");
+    common::draw(&common::example("6_15_pen_modes"), &image, [200, 200], &[]);
+    let svg = common::read(&image);
+    let strokes = common::strokes(&svg);
+    assert_eq!(
+        strokes
+            .iter()
+            .map(|(_, color)| color.as_str())
+            .collect::<Vec<_>>(),
+        ["#ff0000", "#000000", "#ffffff", "#ffffff", "#ffffff"]
+    );
+    // erasing paints the background back over the red line
+    assert_eq!(strokes[1].0, vec![[140, 100], [120, 100]]);
+    // reversing draws the difference, twice over the same line undoes it
+    assert_eq!(svg.matches("mix-blend-mode:difference").count(), 3, "{svg}");
+    assert_eq!(strokes[3].0, vec![[20, 40], [20, 60]]);
+    assert_eq!(strokes[4].0, vec![[20, 60], [20, 40]]);
+}
+
+#[test]
+fn pen_colours_are_checked() {
+    for (name, colour) in [("too-high", "20"), ("negative", "-1"), ("fraction", "2.5")] {
+        let source = format!("SETPENCOLOR \"{colour}\n");
+        let (output, _) = common::run_source("pens", name, &source);
+        assert_eq!(output.status.code(), Some(1), "{colour}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("Invalid pen colour: {colour}")),
+            "{stderr}"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::path::PathBuf;
+
+// the spiral runs past the edge of a 60 x 60 canvas
+fn spiral() -> PathBuf {
+    common::example("4_09_spiral")
+}
+
+fn attribute(svg: &str, name: &str) -> f32 {
+    let start = svg.find(&format!(" {name}=\"")).expect("attribute") + name.len() + 3;
+    let end = start + svg[start..].find('"').expect("attribute end");
+    svg[start..end].parse().expect("number")
+}
+
+// smallest and largest x and y of every stroke point
+fn bounds(svg: &str) -> ((f32, f32), (f32, f32)) {
+    let mut min = (f32::MAX, f32::MAX);
+    let mut max = (f32::MIN, f32::MIN);
+    for (points, _) in common::polylines(svg) {
+        for (x, y) in points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    (min, max)
+}
+
+#[test]
+fn fit_scales_the_drawing_inside_the_margin() {
+    let dir = common::out_dir("placement");
+    common::draw(&spiral(), &dir.join("fit-as-drawn.svg"), [60, 60], &[]);
+    let (min, max) = bounds(&common::read(&dir.join("fit-as-drawn.svg")));
+    assert!(min.0.min(min.1) < 0.0 || max.0.max(max.1) > 60.0);
+
+    let flags = ["--fit", "--margin", "5"];
+    common::draw(&spiral(), &dir.join("fit.svg"), [60, 60], &flags);
+    let svg = common::read(&dir.join("fit.svg"));
+    assert_eq!(attribute(&svg, "width"), 60.0);
+    let (min, max) = bounds(&svg);
+    assert!(min.0 >= 4.99 && min.1 >= 4.99, "{min:?}");
+    assert!(max.0 <= 55.01 && max.1 <= 55.01, "{max:?}");
+    // the longer side touches the margins on both ends
+    assert!((min.0 - 5.0).abs() < 0.01 || (min.1 - 5.0).abs() < 0.01);
+}
+
+#[test]
+fn auto_size_wraps_the_image_around_the_drawing() {
+    let dir = common::out_dir("placement");
+    common::draw(&spiral(), &dir.join("auto-as-drawn.svg"), [60, 60], &[]);
+    let (min, max) = bounds(&common::read(&dir.join("auto-as-drawn.svg")));
+
+    let flags = ["--auto-size", "--margin", "3"];
+    common::draw(&spiral(), &dir.join("auto-size.svg"), [60, 60], &flags);
+    let svg = common::read(&dir.join("auto-size.svg"));
+    assert_eq!(attribute(&svg, "width"), (max.0 - min.0 + 6.0).ceil());
+    assert_eq!(attribute(&svg, "height"), (max.1 - min.1 + 6.0).ceil());
+    let (min, _) = bounds(&svg);
+    assert_eq!(min, (3.0, 3.0));
+}
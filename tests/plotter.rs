@@ -0,0 +1,124 @@
+mod common;
+
+// value after `axis` in a g-code line like "G1 X10 Y20"
+fn axis(line: &str, axis: char) -> Option<f32> {
+    line.split_whitespace()
+        .find_map(|word| word.strip_prefix(axis))
+        .and_then(|v| v.parse().ok())
+}
+
+#[test]
+fn gcode_lifts_the_pen_between_strokes() {
+    let image = common::out_dir("plotter").join("boxes.gcode");
+    common::draw(
+        &common::example("5_00_boxes"),
+        &image,
+        [200, 200],
+        &["--pen-up", "M3 S30", "--pen-down", "M3 S90"],
+    );
+    let gcode = common::read(&image);
+    let lines: Vec<&str> = gcode.lines().collect();
+
+    assert!(lines.contains(&"G21") && lines.contains(&"G90"));
+    let ups = lines.iter().filter(|line| **line == "M3 S30").count();
+    let downs = lines.iter().filter(|line| **line == "M3 S90").count();
+    // lifted once before starting and after every stroke
+    assert_eq!(ups, downs + 1);
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("M0 ; change to the pen for colour")));
+    assert_eq!(lines.last(), Some(&"M2"));
+}
+
+#[test]
+fn gcode_stays_on_the_paper() {
+    // the spiral runs far off the canvas
+    let image = common::out_dir("plotter").join("spiral.gcode");
+    common::draw(
+        &common::example("3_05_spiral"),
+        &image,
+        [200, 200],
+        &["--paper-width", "100", "--paper-height", "50"],
+    );
+    let gcode = common::read(&image);
+    for line in gcode.lines().filter(|line| line.starts_with('G')) {
+        if let (Some(x), Some(y)) = (axis(line, 'X'), axis(line, 'Y')) {
+            assert!(
+                (0.0..=100.0).contains(&x) && (0.0..=50.0).contains(&y),
+                "{line}"
+            );
+            // a square canvas fits the height and is centred across
+            if line.starts_with("G1") {
+                assert!((25.0..=75.0).contains(&x), "{line}");
+            }
+        }
+    }
+}
+
+#[test]
+fn hpgl_selects_a_pen_per_colour() {
+    let image = common::out_dir("plotter").join("boxes.hpgl");
+    common::draw(
+        &common::example("5_00_boxes"),
+        &image,
+        [200, 200],
+        &["--feed-rate", "1200"],
+    );
+    let hpgl = common::read(&image);
+    assert!(hpgl.starts_with("IN;VS2;\n"));
+    assert!(hpgl.contains("SP1;\nPU") && hpgl.contains("SP2;\nPU"));
+    assert!(hpgl.ends_with("PU;SP0;\n"));
+}
+
+#[test]
+fn optimize_drops_repeats_and_travel() {
+    // a square drawn one and a half times, its first side in two halves,
+    // then two dashes with the far one first
+    let dir = common::out_dir("plotter");
+    let side = "TURN \"90\nFORWARD \"40\n";
+    let source = format!(
+        "PENDOWN\nFORWARD \"20\nFORWARD \"20\n{}\
+         PENUP\nFORWARD \"20\nPENDOWN\nFORWARD \"10\n\
+         PENUP\nBACK \"70\nPENDOWN\nFORWARD \"10\n",
+        side.repeat(5)
+    );
+    let program = common::program(&dir, "optimize", &source);
+
+    let travel = |gcode: &str| -> Vec<String> {
+        let moves = gcode.lines().filter(|line| line.starts_with("G0 X"));
+        moves.map(str::to_string).collect()
+    };
+    let drawn = |gcode: &str| {
+        gcode
+            .lines()
+            .filter(|line| line.starts_with("G1 X"))
+            .count()
+    };
+
+    common::draw(&program, &dir.join("plain.gcode"), [200, 200], &[]);
+    common::draw(
+        &program,
+        &dir.join("optimized.gcode"),
+        [200, 200],
+        &["--optimize"],
+    );
+    let plain = common::read(&dir.join("plain.gcode"));
+    let optimized = common::read(&dir.join("optimized.gcode"));
+    assert_eq!(drawn(&plain), 9);
+    // the repeated sides go and the halves join up
+    assert_eq!(drawn(&optimized), 6);
+    // the near dash now comes first
+    assert!(travel(&plain)[1].starts_with("G0 X168 "), "{plain}");
+    assert!(travel(&optimized)[1].starts_with("G0 X105 "), "{optimized}");
+    assert_eq!(travel(&plain).len(), travel(&optimized).len());
+}
+
+#[test]
+fn help_keeps_plotter_options_under_their_heading() {
+    let output = common::rslogo().arg("--help").output().expect("run rslogo");
+    let help = String::from_utf8_lossy(&output.stdout);
+    let plotter = help.split("\nPlotter:\n").nth(1).expect("plotter heading");
+    let plotter = plotter.split("\n\n").next().expect("plotter options");
+    assert!(plotter.contains("--pen-change"), "{help}");
+    assert!(!plotter.contains("--trace"), "{help}");
+}
@@ -0,0 +1,52 @@
+mod common;
+
+#[test]
+fn absolute_moves_and_queries() {
+    let image = common::out_dir("positions").join("positions.svg");
+    common::draw(&common::example("6_00_positions"), &image, [200, 200], &[]);
+    let strokes = common::strokes(&common::read(&image));
+    let white = vec![
+        [100, 100],
+        [150, 100],
+        [150, 50],
+        [100, 50],
+        // SETPOS takes back the POS saved in :corner
+        [150, 50],
+        // TOWARDS and DISTANCE bring it back to the centre
+        [100, 100],
+    ];
+    assert_eq!(
+        strokes,
+        vec![
+            (white, "#ffffff".to_string()),
+            (vec![[100, 100], [120, 100]], "#00ffff".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn addassign_on_a_list_is_an_error() {
+    let dir = common::out_dir("positions");
+    let output = common::run(
+        &common::example("6_01_addassign_list_err"),
+        &dir.join("addassign.svg"),
+        [200, 200],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("ADDASSIGN needs a number"), "{stderr}");
+}
+
+#[test]
+fn setpos_errors_name_setpos() {
+    let (output, _) = common::run_source("positions", "setpos-short", "SETPOS [ \"10 ]\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("command: SETPOS"), "{stderr}");
+
+    let (output, _) = common::run_source("positions", "setxy-short", "SETXY \"10\n");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("command: SETXY"), "{stderr}");
+}
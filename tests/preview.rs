@@ -0,0 +1,74 @@
+mod common;
+
+#[test]
+fn preview_fits_the_terminal() {
+    let dir = common::out_dir("preview");
+    let image = dir.join("spiral.svg");
+    let _ = std::fs::remove_file(&image);
+
+    let output = common::rslogo()
+        .arg(common::example("4_10_spiral_hard"))
+        .arg(&image)
+        .args(["200", "100", "--preview"])
+        .env("COLUMNS", "40")
+        .env("LINES", "21")
+        .output()
+        .expect("run rslogo");
+    assert!(output.status.success(), "{output:?}");
+    let text = String::from_utf8(output.stdout).expect("utf-8 preview");
+    let lines: Vec<&str> = text.lines().collect();
+    // a row is left for the prompt; twice as high as wide at 2x4 dots a character
+    assert_eq!(lines.len(), 20, "{text}");
+    assert!(
+        lines.iter().all(|line| line.chars().count() <= 20),
+        "{text}"
+    );
+    assert!(text.contains('⣿'), "{text}");
+    // not a terminal, so no colours
+    assert!(!text.contains('\x1b'), "{text}");
+    assert!(image.exists());
+}
+
+#[test]
+fn no_save_only_previews() {
+    let dir = common::out_dir("preview");
+    let image = dir.join("unsaved.svg");
+    let _ = std::fs::remove_file(&image);
+
+    let output = common::rslogo()
+        .arg(common::example("4_10_spiral_hard"))
+        .arg(&image)
+        .args(["200", "100", "--preview", "--no-save"])
+        .env("COLUMNS", "40")
+        .env("LINES", "21")
+        .output()
+        .expect("run rslogo");
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains('⣿'));
+    assert!(!image.exists());
+}
+
+#[test]
+fn forced_colours_cover_reversed_cells() {
+    let dir = common::out_dir("preview");
+    let source =
+        "SETPENCOLOR \"2\nPENDOWN\nRIGHT \"20\nPENUP\nRIGHT \"10\nPENREVERSE\nRIGHT \"20\n";
+    let program = common::program(&dir, "reversed", source);
+    let output = common::rslogo()
+        .arg(program)
+        .arg(dir.join("reversed.svg"))
+        .args(["100", "100", "--preview", "--no-save"])
+        .env("COLUMNS", "20")
+        .env("LINES", "11")
+        .env("CLICOLOR_FORCE", "1")
+        .env("COLORTERM", "truecolor")
+        .output()
+        .expect("run rslogo");
+    assert!(output.status.success(), "{output:?}");
+    let text = String::from_utf8(output.stdout).expect("utf-8 preview");
+    // cyan paint, then what reversing the background leaves, in white
+    let cyan = text.find("\x1b[38;2;0;255;255m").expect("cyan");
+    let white = text.find("\x1b[38;2;255;255;255m").expect("white");
+    assert!(cyan < white, "{text:?}");
+    assert!(text.contains("\x1b[0m\n"), "{text:?}");
+}
@@ -0,0 +1,61 @@
+mod common;
+
+#[test]
+fn pdf_page_matches_the_canvas() {
+    let image = common::out_dir("print").join("scaled.pdf");
+    common::draw(
+        &common::example("5_00_boxes"),
+        &image,
+        [200, 100],
+        &["--scale", "2"],
+    );
+    let pdf = common::read(&image);
+    assert!(pdf.starts_with("%PDF-1.4\n"));
+    assert!(pdf.contains("/MediaBox [0 0 200 400]"), "{pdf}");
+    // canvas y points down, so the page is flipped once up front
+    assert!(pdf.contains("stream\n2 0 0 -2 0 400 cm\n"), "{pdf}");
+    assert!(pdf.contains("\n1 0 0 RG\n"), "{pdf}");
+}
+
+#[test]
+fn pdf_cross_references_point_at_objects() {
+    let image = common::out_dir("print").join("plain.pdf");
+    common::draw(&common::example("5_00_boxes"), &image, [200, 100], &[]);
+    let pdf = common::read(&image);
+    let xref: usize = pdf
+        .rsplit("startxref\n")
+        .next()
+        .and_then(|tail| tail.lines().next())
+        .and_then(|offset| offset.parse().ok())
+        .expect("startxref offset");
+    assert!(pdf[xref..].starts_with("xref\n"));
+
+    let entries = pdf[xref..]
+        .lines()
+        .skip(3)
+        .take_while(|line| line.ends_with(" n "));
+    for (i, entry) in entries.enumerate() {
+        let offset: usize = entry[..10].parse().expect("object offset");
+        assert!(
+            pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)),
+            "{entry}"
+        );
+    }
+}
+
+#[test]
+fn eps_is_bounded_by_the_page() {
+    let image = common::out_dir("print").join("transparent.eps");
+    common::draw(
+        &common::example("5_00_boxes"),
+        &image,
+        [200, 100],
+        &["--transparent"],
+    );
+    let eps = common::read(&image);
+    assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 100 200\n"));
+    // no background when transparent
+    assert!(!eps.contains("fill\n"), "{eps}");
+    assert_eq!(eps.matches(" setlinewidth").count(), 1);
+    assert!(eps.ends_with("showpage\n%%EOF\n"));
+}
@@ -0,0 +1,126 @@
+mod common;
+
+type Png = (png::OutputInfo, Option<png::PixelDimensions>, Vec<u8>);
+
+// draws the star example and decodes the png it writes
+fn render(name: &str, flags: &[&str]) -> Png {
+    render_example("5_05_star", name, flags)
+}
+
+fn render_example(example: &str, name: &str, flags: &[&str]) -> Png {
+    let image = common::out_dir("raster").join(format!("{name}.png"));
+    common::draw(&common::example(example), &image, [100, 100], flags);
+
+    let decoder = png::Decoder::new(std::fs::File::open(image).expect("png image"));
+    let mut reader = decoder.read_info().expect("png header");
+    let dims = reader.info().pixel_dims;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("png pixels");
+    pixels.truncate(info.buffer_size());
+    (info, dims, pixels)
+}
+
+fn alphas(pixels: &[u8]) -> Vec<u8> {
+    pixels.chunks(4).map(|rgba| rgba[3]).collect()
+}
+
+#[test]
+fn scale_and_dpi() {
+    let (info, dims, _) = render("scaled", &["--scale", "2.5", "--dpi", "300"]);
+    assert_eq!((info.width, info.height), (250, 250));
+    // 300 dots per inch in pixels per metre
+    let dims = dims.expect("pHYs chunk");
+    assert_eq!((dims.xppu, dims.unit), (11811, png::Unit::Meter));
+}
+
+#[test]
+fn transparent_background() {
+    let (_, _, opaque) = render("opaque", &[]);
+    assert!(alphas(&opaque).iter().all(|a| *a == 255));
+
+    let (_, _, transparent) = render("transparent", &["--transparent"]);
+    assert!(alphas(&transparent).contains(&0));
+}
+
+#[test]
+fn antialiasing_blends_edges() {
+    let partial = |pixels: &[u8]| {
+        alphas(pixels)
+            .iter()
+            .filter(|a| **a != 0 && **a != 255)
+            .count()
+    };
+
+    let (_, _, crisp) = render("crisp", &["--transparent", "--no-antialias"]);
+    let (_, _, smooth) = render("smooth", &["--transparent", "--supersample", "4"]);
+    assert_eq!(partial(&crisp), 0);
+    assert!(partial(&smooth) > 0);
+}
+
+#[test]
+fn curves_are_drawn_round() {
+    let (info, _, pixels) = render_example("6_02_curves", "curves", &[]);
+    // brightest pixel of each colour channel around (x, y)
+    let near = |x: usize, y: usize| {
+        let mut rgb = [0u8; 3];
+        for (px, py) in (x - 1..=x + 1).flat_map(|px| (y - 1..=y + 1).map(move |py| (px, py))) {
+            let i = (py * info.width as usize + px) * 4;
+            for (c, v) in rgb.iter_mut().zip(&pixels[i..i + 3]) {
+                *c = (*c).max(*v);
+            }
+        }
+        rgb
+    };
+    // the circle of radius 10 in cyan, the three quarter arc of radius 30
+    // in white, and nothing in between
+    assert!(
+        near(60, 50)[0] < 64 && near(60, 50)[2] > 128,
+        "{:?}",
+        near(60, 50)
+    );
+    assert!(near(20, 50).iter().all(|c| *c > 128), "{:?}", near(20, 50));
+    assert_eq!(near(50, 50), [0, 0, 0]);
+    assert_eq!(near(35, 50), [0, 0, 0]);
+}
+
+#[test]
+fn pen_modes_erase_and_invert() {
+    let (info, _, pixels) = render_example("6_15_pen_modes", "pen-modes", &[]);
+    let at = |x: usize, y: usize| {
+        let i = (y * info.width as usize + x) * 4;
+        [pixels[i], pixels[i + 1], pixels[i + 2]]
+    };
+    // the red line straddles rows 49 and 50, half covering each
+    assert!(
+        at(55, 49)[0] > 100 && at(55, 49)[1..] == [0, 0],
+        "{:?}",
+        at(55, 49)
+    );
+    // reversed over the background is white, over red it is cyan with the
+    // red half flipped
+    assert_eq!(at(60, 45), [255, 255, 255]);
+    assert_eq!(at(60, 49), [255 - at(55, 49)[0], 255, 255]);
+    // erased, and reversed twice
+    assert_eq!(at(80, 49), [0, 0, 0]);
+    assert_eq!(at(80, 50), [0, 0, 0]);
+    assert_eq!(at(20, 45), [0, 0, 0]);
+}
+
+#[test]
+fn erasing_needs_a_background() {
+    let dir = common::out_dir("raster");
+    let program = common::example("6_15_pen_modes");
+    for name in ["erased.png", "erased.svg", "erased.pdf"] {
+        let output = common::run(&program, &dir.join(name), [100, 100], &["--transparent"]);
+        assert_eq!(output.status.code(), Some(1), "{name}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("transparent background"), "{stderr}");
+    }
+    // plotters leave erasing out
+    common::draw(
+        &program,
+        &dir.join("erased.gcode"),
+        [100, 100],
+        &["--transparent"],
+    );
+}
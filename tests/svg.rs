@@ -0,0 +1,91 @@
+mod common;
+
+#[test]
+fn connected_segments_become_one_polyline() {
+    let image = common::out_dir("svg").join("boxes.svg");
+    common::draw(&common::example("5_00_boxes"), &image, [200, 200], &[]);
+    let svg = common::read(&image);
+    // FORWARD, LEFT, BACK, RIGHT of one box is a single closed stroke
+    assert!(
+        svg.contains("<polyline points=\"100,100 103.422,90.602 "),
+        "{svg}"
+    );
+    assert!(!svg.contains("<path"), "{svg}");
+}
+
+#[test]
+fn precision_limits_decimals() {
+    let image = common::out_dir("svg").join("precision.svg");
+    let flags = ["--svg-precision", "0"];
+    common::draw(&common::example("5_00_boxes"), &image, [200, 200], &flags);
+    let svg = common::read(&image);
+    assert!(svg.contains("<polyline points=\"100,100 103,91 "), "{svg}");
+}
+
+#[test]
+fn groups_wrap_top_level_procedure_calls() {
+    let image = common::out_dir("svg").join("groups.svg");
+    let flags = ["--svg-groups"];
+    common::draw(&common::example("5_00_boxes"), &image, [200, 200], &flags);
+    let svg = common::read(&image);
+    assert!(svg.contains("<g id=\"Box-1\">\n<polyline"), "{svg}");
+    assert!(svg.contains("<g id=\"Box-2\">"), "{svg}");
+    assert_eq!(svg.matches("<g ").count(), svg.matches("</g>").count());
+}
+
+#[test]
+fn groups_left_open_by_an_error_are_closed() {
+    let dir = common::out_dir("svg");
+    let source = "TO Box\n  PENDOWN\n  FORWARD \"10\n  POPTURTLE\nEND\nBox\n";
+    let program = common::program(&dir, "open-group", source);
+    let image = dir.join("open-group.svg");
+    let flags = ["--keep-partial", "--svg-groups"];
+    let output = common::run(&program, &image, [200, 200], &flags);
+    assert_eq!(output.status.code(), Some(1));
+    let svg = common::read(&image);
+    assert!(svg.ends_with("<polyline points=\"100,100 100,90\" fill=\"none\" stroke=\"#ffffff\"/>\n</g>\n</svg>\n"), "{svg}");
+}
+
+#[test]
+fn replay_keeps_the_groups() {
+    let dir = common::out_dir("svg");
+    let trace = dir.join("groups.jsonl");
+    let trace_arg = trace.to_str().expect("path");
+    let drawn = dir.join("groups-run.svg");
+    let flags = ["--svg-groups", "--emit-trace", trace_arg];
+    common::draw(&common::example("5_00_boxes"), &drawn, [200, 200], &flags);
+
+    let replayed = dir.join("groups-replay.svg");
+    let status = common::rslogo()
+        .arg("replay")
+        .arg(&trace)
+        .arg(&replayed)
+        .arg("--svg-groups")
+        .status()
+        .expect("run rslogo");
+    assert!(status.success());
+    let svg = common::read(&replayed);
+    assert!(svg.contains("<g id=\"Box-2\">"), "{svg}");
+    assert_eq!(svg, common::read(&drawn));
+}
+
+#[test]
+fn layers_hold_one_pen_each() {
+    let image = common::out_dir("svg").join("layers.svg");
+    common::draw(
+        &common::example("5_00_boxes"),
+        &image,
+        [200, 200],
+        &["--layers"],
+    );
+    let svg = common::read(&image);
+    assert!(svg.contains("xmlns:inkscape="), "{svg}");
+    let layers = svg.matches("inkscape:groupmode=\"layer\"").count();
+    assert_eq!(layers, 5, "{svg}");
+    // every red box lands in one layer, and as they all start at the centre
+    // they join into a single polyline
+    let red = svg.split("id=\"pen-4\">").nth(1).expect("red layer");
+    let red = &red[..red.find("</g>").expect("layer end")];
+    assert_eq!(red.matches("<polyline").count(), 1, "{red}");
+    assert_eq!(red.matches(" 100,100").count(), 7, "{red}");
+}
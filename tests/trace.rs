@@ -0,0 +1,152 @@
+mod common;
+
+const SOURCE: &str = "PENDOWN\nTURN \"90\nFORWARD \"10\nSETPENCOLOR \"2\n";
+
+#[test]
+fn trace_goes_to_stderr() {
+    let dir = common::out_dir("trace");
+    let program = common::program(&dir, "text", SOURCE);
+    let output = common::draw(&program, &dir.join("text.svg"), [100, 100], &["--trace"]);
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "line 1: PENDOWN -> turtle 0 at (50, 50) heading 0\n\
+         line 2: TURN 90 -> turtle 0 at (50, 50) heading 90\n\
+         line 3: FORWARD 10 -> turtle 0 at (60, 50) heading 90\n\
+         line 4: SETPENCOLOR 2 -> turtle 0 at (60, 50) heading 90\n"
+    );
+}
+
+#[test]
+fn json_lines_carry_the_command_and_the_state_it_leaves() {
+    let dir = common::out_dir("trace");
+    let program = common::program(&dir, "json", SOURCE);
+    let flags = ["--trace", "--trace-format", "json"];
+    let output = common::draw(&program, &dir.join("json.svg"), [100, 100], &flags);
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            r#"{"line":1,"command":"PENDOWN","args":[],"turtle":0,"x":50,"y":50,"heading":0}"#,
+            r#"{"line":2,"command":"TURN","args":[90],"turtle":0,"x":50,"y":50,"heading":90}"#,
+            r#"{"line":3,"command":"FORWARD","args":[10],"turtle":0,"x":60,"y":50,"heading":90}"#,
+            r#"{"line":4,"command":"SETPENCOLOR","args":[2],"turtle":0,"x":60,"y":50,"heading":90}"#,
+        ]
+    );
+}
+
+#[test]
+fn trace_output_takes_the_trace_off_stderr() {
+    let dir = common::out_dir("trace");
+    let program = common::program(&dir, "to-file", SOURCE);
+    let trace = dir.join("to-file.jsonl");
+    let trace_arg = trace.to_str().expect("path");
+    let flags = [
+        "--trace",
+        "--trace-format",
+        "json",
+        "--trace-output",
+        trace_arg,
+    ];
+    let output = common::draw(&program, &dir.join("to-file.svg"), [100, 100], &flags);
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty(), "{output:?}");
+    let written = common::read(&trace);
+    assert_eq!(written.lines().count(), 4, "{written}");
+    assert!(
+        written.starts_with(r#"{"line":1,"command":"PENDOWN""#),
+        "{written}"
+    );
+}
+
+#[test]
+fn trace_options_need_trace() {
+    let dir = common::out_dir("trace");
+    let program = common::program(&dir, "no-trace", SOURCE);
+    for flags in [
+        &["--trace-format", "json"][..],
+        &["--trace-output", "x.txt"],
+    ] {
+        let output = common::run(&program, &dir.join("no-trace.svg"), [100, 100], flags);
+        assert!(!output.status.success(), "{flags:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("--trace"),
+            "{output:?}"
+        );
+    }
+}
+
+#[test]
+fn replay_takes_both_sizes_or_none() {
+    let dir = common::out_dir("trace");
+    let trace = dir.join("sized.jsonl");
+    let program = common::program(&dir, "sized", SOURCE);
+    let trace_arg = trace.to_str().expect("path");
+    common::draw(
+        &program,
+        &dir.join("sized.svg"),
+        [100, 100],
+        &["--emit-trace", trace_arg],
+    );
+
+    let replay = |sizes: &[&str]| {
+        common::rslogo()
+            .arg("replay")
+            .arg(&trace)
+            .arg(dir.join("sized-replay.svg"))
+            .args(sizes)
+            .output()
+            .expect("run rslogo")
+    };
+    assert!(replay(&[]).status.success());
+    assert!(replay(&["50", "80"]).status.success());
+    let output = replay(&["50"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("<WIDTH>"), "{stderr}");
+}
+
+#[test]
+fn replay_reads_back_quotes_backslashes_and_unicode() {
+    let dir = common::out_dir("trace");
+    let trace = dir.join("escapes.jsonl");
+    let source = "LABEL [ say \"hi\\\" back\\\\ é 日本 ]\nSETLABELFONT \"Noto\"Sans\nLABEL \"x\n";
+    let program = common::program(&dir, "escapes", source);
+    let trace_arg = trace.to_str().expect("path");
+    let drawn = dir.join("escapes.svg");
+    common::draw(&program, &drawn, [100, 100], &["--emit-trace", trace_arg]);
+
+    let replayed = dir.join("escapes-replay.svg");
+    let status = common::rslogo()
+        .arg("replay")
+        .arg(&trace)
+        .arg(&replayed)
+        .status()
+        .expect("run rslogo");
+    assert!(status.success());
+    let svg = common::read(&replayed);
+    assert!(
+        svg.contains(">say hi\\&quot; back\\\\ é 日本</text>"),
+        "{svg}"
+    );
+    assert!(svg.contains("font-family=\"Noto&quot;Sans\""), "{svg}");
+    assert_eq!(svg, common::read(&drawn));
+
+    // escapes other writers use
+    let trace = dir.join("escaped-by-hand.jsonl");
+    let line = r#"{"line":1,"command":"LABEL","args":["caf\u00e9\t\"\\\/"],"turtle":0,"x":50,"y":50,"heading":0}"#;
+    let header = r#"{"width":100,"height":100,"coordinates":"canvas"}"#;
+    std::fs::write(&trace, format!("{header}\n{line}\n")).expect("trace");
+    let replayed = dir.join("escaped-by-hand.svg");
+    let status = common::rslogo()
+        .arg("replay")
+        .arg(&trace)
+        .arg(&replayed)
+        .status()
+        .expect("run rslogo");
+    assert!(status.success());
+    let svg = common::read(&replayed);
+    assert!(svg.contains(">café\t&quot;\\/</text>"), "{svg}");
+}
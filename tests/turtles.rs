@@ -0,0 +1,122 @@
+mod common;
+
+#[test]
+fn every_turtle_keeps_its_own_state() {
+    let image = common::out_dir("turtles").join("turtles.svg");
+    common::draw(&common::example("6_05_turtles"), &image, [200, 200], &[]);
+    let (white, cyan) = ("#ffffff".to_string(), "#00ffff".to_string());
+    assert_eq!(
+        common::strokes(&common::read(&image)),
+        vec![
+            (vec![[100, 100], [100, 90]], white.clone()),
+            (vec![[100, 100], [120, 100]], cyan.clone()),
+            // turtle 2 starts with the default pen
+            (vec![[100, 100], [100, 120]], white.clone()),
+            (vec![[120, 100], [130, 100]], cyan),
+            (vec![[100, 90], [100, 80]], white),
+        ]
+    );
+}
+
+#[test]
+fn turtle_ids_are_whole_and_not_negative() {
+    let dir = common::out_dir("turtles");
+    for (name, id) in [
+        ("6_06_tell_fraction_err", "1.5"),
+        ("6_07_ask_negative_err", "-1"),
+    ] {
+        let image = dir.join(format!("{name}.svg"));
+        let output = common::run(&common::example(name), &image, [200, 200], &[]);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!("Invalid turtle id: {id}")),
+            "{stderr}"
+        );
+    }
+}
+
+#[test]
+fn popturtle_restores_what_pushturtle_saved() {
+    let image = common::out_dir("turtles").join("stack.svg");
+    common::draw(
+        &common::example("6_08_turtle_stack"),
+        &image,
+        [200, 200],
+        &[],
+    );
+    let svg = common::read(&image);
+    assert_eq!(
+        common::points(&svg),
+        vec![
+            vec![[100, 100], [114, 86]],
+            // back where it was pushed, facing up again with the pen down
+            vec![[100, 100], [100, 90]],
+            vec![[100, 100], [130, 100]],
+            vec![[100, 90], [100, 100], [90, 100]],
+            vec![[100, 90], [100, 85]],
+        ]
+    );
+    let strokes = common::strokes(&svg);
+    assert_eq!(strokes[0].1, "#ff0000");
+    assert_eq!(strokes[1].1, "#ffffff");
+}
+
+#[test]
+fn turtles_do_not_share_their_stacks() {
+    let image = common::out_dir("turtles").join("pop-empty.svg");
+    let output = common::run(
+        &common::example("6_09_pop_empty_err"),
+        &image,
+        [200, 200],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("POPTURTLE with no saved turtle state"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn shown_turtles_are_drawn_where_they_stop() {
+    let image = common::out_dir("turtles").join("glyphs.svg");
+    common::draw(
+        &common::example("6_13_turtle_glyphs"),
+        &image,
+        [200, 200],
+        &[],
+    );
+    let svg = common::read(&image);
+    // turtle 0 turned to heading 90, so its nose points right
+    assert!(
+        svg.contains("<polygon points=\"130,100 114,106 114,94\" fill=\"#ffffff\"/>"),
+        "{svg}"
+    );
+    // turtle 1 keeps its own shape and pen colour
+    assert!(
+        svg.contains("<polygon points=\"100,126 104,134 96,134\" fill=\"#00ffff\"/>"),
+        "{svg}"
+    );
+    assert!(
+        svg.contains("<polygon points=\"100,50 106,66 94,66\" fill=\"#ffffff\"/>"),
+        "{svg}"
+    );
+    // turtle 2 was hidden again
+    assert_eq!(svg.matches("<polygon").count(), 3, "{svg}");
+}
+
+#[test]
+fn shapes_need_three_corners() {
+    let image = common::out_dir("turtles").join("corners.svg");
+    let output = common::run(
+        &common::example("6_14_setshape_corners_err"),
+        &image,
+        [200, 200],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown turtle shape: 1 2 3 4"), "{stderr}");
+}